@@ -0,0 +1,138 @@
+use std::borrow::Borrow;
+
+use deluxe::{ParseMetaItem, ParseMode};
+use proc_macro2::Span;
+use quote::ToTokens;
+use syn::{
+    parse::{ParseBuffer, ParseStream},
+    punctuated::Punctuated,
+    GenericArgument, GenericParam, Generics, Ident, LitStr, PathArguments, Token, Type, TypePath,
+    WherePredicate,
+};
+
+/// Where-predicates written by hand in `#[debug(bound = "...")]`.
+///
+/// Each string is either a full list of where-predicates, like
+/// `"T::Item: Debug + Clone"`, or just a type, like `"T::Value"`, which is
+/// shorthand for requiring that type to implement `Debug`. Several strings can
+/// be given at once as `bound("...", "...")`.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Bounds(pub(crate) Vec<WherePredicate>);
+
+impl ParseMetaItem for Bounds {
+    fn parse_meta_item(input: ParseStream, _mode: ParseMode) -> deluxe::Result<Self> {
+        let lit: LitStr = input.parse()?;
+        Ok(Bounds(parse_bound(&lit)?))
+    }
+
+    fn parse_meta_item_inline<'s, S: Borrow<ParseBuffer<'s>>>(
+        inputs: &[S],
+        _mode: ParseMode,
+    ) -> deluxe::Result<Self> {
+        let mut predicates = Vec::new();
+        for input in inputs {
+            let lits = Punctuated::<LitStr, Token![,]>::parse_terminated(input.borrow())?;
+            for lit in lits {
+                predicates.extend(parse_bound(&lit)?);
+            }
+        }
+        Ok(Bounds(predicates))
+    }
+
+    fn missing_meta_item(_name: &str, _span: Span) -> deluxe::Result<Self> {
+        Ok(Bounds::default())
+    }
+}
+
+fn parse_bound(lit: &LitStr) -> syn::Result<Vec<WherePredicate>> {
+    match lit.parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated) {
+        Ok(predicates) => Ok(predicates.into_iter().collect()),
+        Err(err) => match lit.parse::<Type>() {
+            Ok(ty) => Ok(vec![syn::parse_quote!(#ty: ::std::fmt::Debug)]),
+            Err(_) => Err(err),
+        },
+    }
+}
+
+/// The type parameters declared by the input, which are the only names the
+/// inferred bounds can refer to.
+pub(crate) fn type_params(generics: &Generics) -> Vec<&Ident> {
+    generics
+        .params
+        .iter()
+        .filter_map(|p| match p {
+            GenericParam::Type(param) => Some(&param.ident),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Collects the types that need a `Debug` bound for a field of type `ty` to be
+/// printable: every type parameter mentioned in it and every associated type
+/// of a type parameter, like `T::Value`.
+///
+/// Anything inside a `PhantomData` is ignored since `PhantomData<T>` implements
+/// `Debug` regardless of `T`.
+pub(crate) fn bounded_types(ty: &Type, params: &[&Ident], bounded: &mut Vec<Type>) {
+    match ty {
+        Type::Path(TypePath {
+            qself: Some(qself), ..
+        }) => {
+            let mut inner = Vec::new();
+            bounded_types(&qself.ty, params, &mut inner);
+            if !inner.is_empty() {
+                push(bounded, ty);
+            }
+        }
+        Type::Path(TypePath { qself: None, path }) => {
+            if path.leading_colon.is_none()
+                && params.iter().any(|param| path.segments[0].ident == **param)
+            {
+                push(bounded, ty);
+                return;
+            }
+            if path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "PhantomData")
+            {
+                return;
+            }
+            for segment in &path.segments {
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    for arg in &args.args {
+                        match arg {
+                            GenericArgument::Type(ty) => bounded_types(ty, params, bounded),
+                            GenericArgument::AssocType(assoc) => {
+                                bounded_types(&assoc.ty, params, bounded)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+        Type::Array(array) => bounded_types(&array.elem, params, bounded),
+        Type::Group(group) => bounded_types(&group.elem, params, bounded),
+        Type::Paren(paren) => bounded_types(&paren.elem, params, bounded),
+        Type::Ptr(ptr) => bounded_types(&ptr.elem, params, bounded),
+        Type::Reference(reference) => bounded_types(&reference.elem, params, bounded),
+        Type::Slice(slice) => bounded_types(&slice.elem, params, bounded),
+        Type::Tuple(tuple) => {
+            for elem in &tuple.elems {
+                bounded_types(elem, params, bounded);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn push(bounded: &mut Vec<Type>, ty: &Type) {
+    let key = ty.to_token_stream().to_string();
+    if !bounded
+        .iter()
+        .any(|other| other.to_token_stream().to_string() == key)
+    {
+        bounded.push(ty.clone());
+    }
+}
@@ -0,0 +1,136 @@
+use deluxe::{parse_attributes, HasAttributes, ParseAttributes};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    ext::IdentExt, parse_quote_spanned, spanned::Spanned, Data, DataEnum, DataStruct, DataUnion,
+    DeriveInput, Fields, LitStr, WherePredicate,
+};
+
+use crate::{
+    bound::type_params,
+    local, pattern,
+    template::{self, Reference},
+};
+
+/// The template in `#[display("...")]`.
+#[derive(ParseAttributes, Clone, Debug)]
+#[deluxe(attributes(display))]
+struct DisplayAttributes(LitStr);
+
+pub(crate) fn derive(derive_input: DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &derive_input.ident;
+    let fmt = local("fmt");
+    let mut references = Vec::new();
+    let body = match &derive_input.data {
+        Data::Struct(DataStruct { fields, .. }) => {
+            let template = match template(&derive_input)? {
+                Some(template) => template,
+                None => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "missing `#[display(\"...\")]` attribute",
+                    ))
+                }
+            };
+            let write = write(&template, fields, &mut references)?;
+            let pattern = pattern(quote!(Self), fields);
+            quote!(
+                match self {
+                    #pattern => #write,
+                }
+            )
+        }
+        Data::Enum(DataEnum { variants, .. }) => {
+            if let Some(attr) = derive_input
+                .attrs
+                .iter()
+                .find(|attr| attr.path().is_ident("display"))
+            {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "`#[display(\"...\")]` on an enum has no effect, put a template on each variant instead",
+                ));
+            }
+            let mut arms = Vec::new();
+            for variant in variants {
+                let variant_ident = &variant.ident;
+                let write = match (template(variant)?, &variant.fields) {
+                    (Some(template), fields) => write(&template, fields, &mut references)?,
+                    (None, Fields::Unit) => {
+                        let name = variant_ident.unraw().to_string();
+                        quote!(#fmt.write_str(#name))
+                    }
+                    (None, _) => {
+                        return Err(syn::Error::new(
+                            variant_ident.span(),
+                            "missing `#[display(\"...\")]` attribute on variant with fields",
+                        ))
+                    }
+                };
+                let pattern = pattern(quote!(Self::#variant_ident), &variant.fields);
+                arms.push(quote!(#pattern => #write,));
+            }
+            if arms.is_empty() {
+                quote!(match *self {})
+            } else {
+                quote!(
+                    match self {
+                        #(#arms)*
+                    }
+                )
+            }
+        }
+        Data::Union(DataUnion { union_token, .. }) => {
+            return Err(syn::Error::new_spanned(
+                union_token,
+                "CustomDisplay cannot be applied to unions",
+            ))
+        }
+    };
+    let params = type_params(&derive_input.generics);
+    let mut predicates: Vec<WherePredicate> = Vec::new();
+    for reference in references {
+        let fmt_trait = &reference.fmt_trait;
+        predicates.extend(
+            reference
+                .bounded_types(&params)
+                .iter()
+                .map(|ty| parse_quote_spanned!(ty.span() => #ty: #fmt_trait)),
+        );
+    }
+    let mut generics = derive_input.generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote!(
+        impl #impl_generics ::std::fmt::Display for #ident #ty_generics #where_clause {
+            fn fmt(&self, #fmt: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                #body
+            }
+        }
+    ))
+}
+
+fn template<T: HasAttributes>(obj: &T) -> syn::Result<Option<LitStr>> {
+    if obj
+        .attrs()
+        .iter()
+        .any(|attr| attr.path().is_ident("display"))
+    {
+        let DisplayAttributes(template) = parse_attributes(obj)?;
+        Ok(Some(template))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Builds the `write!` call for a template.
+fn write<'a>(
+    template: &LitStr,
+    fields: &'a Fields,
+    references: &mut Vec<Reference<'a>>,
+) -> syn::Result<TokenStream> {
+    let fields: Vec<_> = fields.iter().collect();
+    let rewritten = template::rewrite(template, &fields, None, references)?;
+    let fmt = local("fmt");
+    Ok(quote!(::std::write!(#fmt, #rewritten)))
+}
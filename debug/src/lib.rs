@@ -1,12 +1,54 @@
-use deluxe::{parse_attributes, ParseAttributes};
-use proc_macro2::TokenStream;
-use quote::{quote, quote_spanned};
-use syn::{spanned::Spanned, Data, DataStruct, DeriveInput, Field, Fields, GenericParam, Generics};
+use deluxe::{parse_attributes, ParseAttributes, ParseMetaFlatNamed, ParseMetaItem, ParseMode};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    ext::IdentExt, parse::ParseStream, parse_quote_spanned, punctuated::Punctuated,
+    spanned::Spanned, Attribute, Data, DataEnum, DataStruct, DataUnion, DeriveInput, Expr, ExprLit,
+    Field, Fields, Generics, Lit, LitStr, Meta, Token, Type, TypePath, Variant, WherePredicate,
+};
 
-#[derive(ParseAttributes, Clone, Debug, Default)]
+use crate::{
+    bound::{bounded_types, type_params, Bounds},
+    rename::RenameRule,
+};
+
+mod bound;
+mod display;
+mod rename;
+mod template;
+
+const REDACTED: &str = "<redacted>";
+
+#[derive(ParseAttributes, ParseMetaItem, Clone, Debug, Default)]
 #[deluxe(attributes(debug))]
 struct DebugFieldAttributes {
-    format: Option<String>,
+    format: Option<LitStr>,
+    skip: Option<()>,
+    redact: Option<Redact>,
+    #[deluxe(with = deluxe::with::maybe_quoted)]
+    with: Option<syn::Path>,
+    rename: Option<String>,
+    max_len: Option<usize>,
+    #[deluxe(with = deluxe::with::maybe_quoted)]
+    skip_if: Option<syn::Path>,
+    bound: Option<Bounds>,
+    inline: Option<()>,
+    flatten: Option<()>,
+}
+
+/// Either `redact` on its own, using the container's placeholder, or
+/// `redact = "..."` with a placeholder for this field only.
+#[derive(Clone, Debug)]
+struct Redact(Option<String>);
+
+impl ParseMetaItem for Redact {
+    fn parse_meta_item(input: ParseStream, mode: ParseMode) -> deluxe::Result<Self> {
+        String::parse_meta_item(input, mode).map(|placeholder| Redact(Some(placeholder)))
+    }
+
+    fn parse_meta_item_flag(_span: Span) -> deluxe::Result<Self> {
+        Ok(Redact(None))
+    }
 }
 
 deluxe::define_with_collection!(
@@ -15,122 +57,941 @@ deluxe::define_with_collection!(
     Vec<String>
 );
 
-#[derive(ParseAttributes, Clone, Debug, Default)]
+#[derive(ParseAttributes, ParseMetaItem, Clone, Debug, Default)]
 #[deluxe(attributes(debug))]
 struct DebugStructAttributes {
     #[deluxe(default = Vec::new())]
     #[deluxe(with = mod_path_vec)]
     unbound: Vec<String>,
+    bound: Option<Bounds>,
     #[deluxe(default = Vec::new())]
     #[deluxe(with = mod_path_vec)]
-    bound: Vec<String>,
+    redact_all_except: Vec<String>,
+    placeholder: Option<String>,
+    rename: Option<String>,
+    rename_all: Option<RenameRule>,
+    max_len: Option<usize>,
+    non_exhaustive: Option<()>,
+    visit_fields: Option<()>,
+    /// The union field to print. Reading it is only sound if every value
+    /// that is formatted has that field initialized, which the derive cannot
+    /// check, hence the `unsafe_` prefix.
+    unsafe_union_as: Option<String>,
+}
+
+#[derive(ParseAttributes, ParseMetaItem, Clone, Debug, Default)]
+#[deluxe(attributes(debug))]
+struct DebugVariantAttributes {
+    rename: Option<String>,
+    rename_all: Option<RenameRule>,
+    non_exhaustive: Option<()>,
 }
 
-type MaybeAttr = Result<DebugFieldAttributes, syn::Error>;
-type FieldAndAttr = (Field, MaybeAttr);
+type FieldAndAttr = (Field, DebugFieldAttributes);
 type FieldsAndAttrs = Vec<FieldAndAttr>;
 
-// #[allow(unused)]
+/// Where the generated code sends each field.
+#[derive(Clone, Copy)]
+enum Target {
+    /// A `DebugStruct` or `DebugTuple` named `debug_builder`.
+    Builder,
+    /// A `&mut dyn FnMut(&'static str, &dyn Debug)` named `visitor`.
+    Visitor,
+}
+
+/// A local variable of the generated code. Its mixed-site span keeps it from
+/// shadowing the paths in the user's attributes, such as `#[debug(with = fmt)]`.
+fn local(name: &str) -> Ident {
+    Ident::new(name, Span::mixed_site())
+}
+
 #[proc_macro_derive(CustomDebug, attributes(debug))]
 pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let derive_input: DeriveInput = syn::parse(input).unwrap();
-    match derive_input.data {
-        Data::Struct(DataStruct {
-            fields: Fields::Named(fields),
-            ..
-        }) => {
-            let attrs: DebugStructAttributes = match parse_attributes(&derive_input.attrs) {
-                Ok(attrs) => attrs,
+    let attrs: DebugStructAttributes = match debug_attributes(&derive_input.attrs) {
+        Ok((attrs, _)) => attrs,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let ident = &derive_input.ident;
+    if let (Some(_), Data::Struct(_) | Data::Enum(_)) = (&attrs.unsafe_union_as, &derive_input.data)
+    {
+        return syn::Error::new(
+            ident.span(),
+            "`unsafe_union_as` is only supported on unions",
+        )
+        .to_compile_error()
+        .into();
+    }
+    if let Err(err) = check_redact_all_except(&derive_input.attrs, &derive_input.data) {
+        return err.to_compile_error().into();
+    }
+    let packed = is_packed(&derive_input.attrs);
+    let scrutinee = if packed { quote!(*self) } else { quote!(self) };
+    let name = match &attrs.rename {
+        Some(rename) => rename.clone(),
+        None => ident.unraw().to_string(),
+    };
+    let mut all_fields = Vec::new();
+    let mut methods = Vec::new();
+    let mut visit_arms = Vec::new();
+    let body = match &derive_input.data {
+        Data::Struct(DataStruct { fields, .. }) => {
+            let fields_attrs = match fields_attrs(fields) {
+                Ok(fields_attrs) => fields_attrs,
                 Err(err) => return err.to_compile_error().into(),
             };
-            let fields_attrs: FieldsAndAttrs = fields
-                .named
-                .iter()
-                .map(|f| (f.clone(), parse_attributes(&f.attrs)))
-                .collect();
-            if let Some((_, err)) = fields_attrs.iter().find(|(_, attr)| attr.is_err()) {
-                return err.clone().unwrap_err().to_compile_error().into();
-            }
-            let ident = &derive_input.ident;
-            let method_calls = method_calls(&fields_attrs);
-            let (impl_generics, generics, where_clause) = generics(&derive_input.generics, &attrs);
-
-            // let result = quote!(
-            //     impl <#impl_generics> ::std::fmt::Debug for #ident <#generics> where #where_clause {
-            //         fn fmt(&self, fmt: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
-            //             let mut debug_struct = fmt.debug_struct(stringify!(#ident));
-            //             #(#method_calls)*
-            //             debug_struct.finish()
-            //         }
-            //     }
-            // );
-            // println!("{}", result);
-
+            all_fields.push(fields_attrs.clone());
+            let pattern = pattern(quote!(Self), fields);
+            let locals = locals(fields, packed);
+            let visits = method_calls(&fields_attrs, &attrs, attrs.rename_all, Target::Visitor);
+            visit_arms.push(quote!(#pattern => { #locals #(#visits)* }));
+            let body = debug_fields(
+                &name,
+                fields,
+                &fields_attrs,
+                &attrs,
+                attrs.rename_all,
+                attrs.non_exhaustive.is_some(),
+            );
             quote!(
-                impl <#impl_generics> ::std::fmt::Debug for #ident <#generics> where #where_clause {
-                    fn fmt(&self, fmt: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
-                        let mut debug_struct = fmt.debug_struct(stringify!(#ident));
-                        #(#method_calls)*
-                        debug_struct.finish()
+                match #scrutinee {
+                    #pattern => {
+                        #locals
+                        #body
                     }
                 }
             )
+        }
+        Data::Enum(DataEnum { enum_token, .. }) if attrs.rename.is_some() => {
+            return syn::Error::new_spanned(
+                enum_token,
+                "`rename` has no effect on enums, rename the variants instead",
+            )
+            .to_compile_error()
             .into()
         }
-        _ => panic!("CustomDebug can only be applied to structs with named fields"),
+        Data::Enum(DataEnum { variants, .. }) => {
+            match variant_arms(variants, &attrs, &mut all_fields, &mut visit_arms) {
+                Ok(arms) if arms.is_empty() => quote!(match *self {}),
+                Ok(arms) => quote!(
+                    match self {
+                        #(#arms)*
+                    }
+                ),
+                Err(err) => return err.to_compile_error().into(),
+            }
+        }
+        Data::Union(DataUnion { union_token, .. }) if attrs.unsafe_union_as.is_none() => {
+            return syn::Error::new_spanned(
+                union_token,
+                "CustomDebug on a union needs `#[debug(unsafe_union_as = \"field\")]` to choose which field to print, \
+                 promising that the field is initialized whenever the union is formatted",
+            )
+            .to_compile_error()
+            .into()
+        }
+        Data::Union(DataUnion { fields, .. }) => {
+            let fields = Fields::Named(fields.clone());
+            let union_as = attrs.unsafe_union_as.as_deref().unwrap_or_default();
+            let (index, field_attr) = match fields_attrs(&fields).map(|fields_attrs| {
+                fields_attrs
+                    .into_iter()
+                    .enumerate()
+                    .find(|(i, (f, _))| member(*i, f) == union_as)
+            }) {
+                Ok(Some(found)) => found,
+                Ok(None) => {
+                    return syn::Error::new(
+                        ident.span(),
+                        format!("`unsafe_union_as` names no field `{}`", union_as),
+                    )
+                    .to_compile_error()
+                    .into()
+                }
+                Err(err) => return err.to_compile_error().into(),
+            };
+            let binding = binding(index, &field_attr.0);
+            let member = &field_attr.0.ident;
+            // Reading the field is only sound if it is initialized in every
+            // value formatted. The derive cannot check that, so the attribute
+            // is named `unsafe_union_as` to make the caller's promise visible.
+            let read = if packed {
+                quote!(let #binding = &unsafe { self.#member };)
+            } else {
+                quote!(let #binding = unsafe { &self.#member };)
+            };
+            let chosen = vec![field_attr];
+            all_fields.push(chosen.clone());
+            let visits = method_calls(&chosen, &attrs, attrs.rename_all, Target::Visitor);
+            visit_arms.push(quote!(_ => { #read #(#visits)* }));
+            let body = debug_fields(
+                &name,
+                &fields,
+                &chosen,
+                &attrs,
+                attrs.rename_all,
+                attrs.non_exhaustive.is_some(),
+            );
+            quote!(
+                #read
+                #body
+            )
+        }
+    };
+    let generics = generics(&derive_input.generics, &attrs, &all_fields);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    if attrs.visit_fields.is_some() {
+        let body = if visit_arms.is_empty() {
+            quote!(match *self {})
+        } else {
+            quote!(
+                match #scrutinee {
+                    #(#visit_arms)*
+                }
+            )
+        };
+        let visitor = local("visitor");
+        methods.push(quote!(
+            /// Calls `visitor` with the name and value of every field that
+            /// the `Debug` impl would print, in the same order.
+            pub fn visit_fields(
+                &self,
+                #visitor: &mut dyn ::std::ops::FnMut(&'static str, &dyn ::std::fmt::Debug),
+            ) {
+                #body
+            }
+        ));
     }
+    let methods_impl = (!methods.is_empty()).then(|| {
+        quote!(
+            impl #impl_generics #ident #ty_generics #where_clause {
+                #(#methods)*
+            }
+        )
+    });
+    let fmt = local("fmt");
+    quote!(
+        impl #impl_generics ::std::fmt::Debug for #ident #ty_generics #where_clause {
+            fn fmt(&self, #fmt: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                #body
+            }
+        }
+
+        #methods_impl
+    )
+    .into()
 }
 
-fn generics(
-    generics: &Generics,
-    attrs: &DebugStructAttributes,
-) -> (TokenStream, TokenStream, TokenStream) {
-    let params = &generics.params;
-    let impl_generics = quote!(#params);
-    let mut generic_types: Vec<_> = params
+#[proc_macro_derive(CustomDisplay, attributes(display))]
+pub fn derive_display(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let derive_input: DeriveInput = syn::parse(input).unwrap();
+    display::derive(derive_input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Parses the `#[debug(...)]` attributes in `attrs`, first checking every key
+/// so that a typo is reported along with the keys `T` accepts.
+///
+/// `#[debug = "..."]` and `#[debug("...")]` are shorthand for `format` and are
+/// returned separately. They are only accepted where `T` has a `format` key.
+fn debug_attributes<T>(attrs: &[Attribute]) -> syn::Result<(T, Option<LitStr>)>
+where
+    T: for<'t> ParseAttributes<'t, Vec<Attribute>> + ParseMetaFlatNamed,
+{
+    let keys = T::field_names();
+    let expected = keys
         .iter()
-        .filter_map(|p| match p {
-            GenericParam::Type(syn::TypeParam { ident: ty, .. })
-                if !attrs.unbound.contains(&format!("{}", ty)) =>
-            {
-                Some(quote_spanned!(
-                    ty.span() => #ty: ::std::fmt::Debug
+        .map(|key| format!("`{}`", key))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut shorthand: Option<LitStr> = None;
+    let mut keyed = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("debug")) {
+        let (template, form) = match &attr.meta {
+            Meta::NameValue(name_value) => match &name_value.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(lit), ..
+                }) => (lit.clone(), "#[debug = \"...\"]"),
+                value => {
+                    return Err(syn::Error::new_spanned(
+                        value,
+                        "expected a format string, as in `#[debug = \"...\"]`",
+                    ))
+                }
+            },
+            Meta::List(list) => match list.parse_args::<LitStr>() {
+                Ok(lit) => (lit, "#[debug(\"...\")]"),
+                Err(_) => {
+                    // Anything that does not even parse as a list of keys is
+                    // left for deluxe to report.
+                    if let Ok(metas) =
+                        list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                    {
+                        for meta in metas {
+                            let path = meta.path();
+                            if !keys.iter().any(|key| path.is_ident(key)) {
+                                return Err(syn::Error::new_spanned(
+                                    path,
+                                    format!(
+                                        "unknown key `{}` in `#[debug(...)]`, expected one of {}",
+                                        quote!(#path),
+                                        expected
+                                    ),
+                                ));
+                            }
+                        }
+                    }
+                    keyed.push(attr.clone());
+                    continue;
+                }
+            },
+            Meta::Path(path) => {
+                return Err(syn::Error::new_spanned(
+                    path,
+                    format!("expected `#[debug(...)]` with one of {}", expected),
                 ))
             }
-            _ => None,
+        };
+        if !keys.contains(&"format") {
+            return Err(syn::Error::new_spanned(
+                attr,
+                format!(
+                    "`{}` is only supported on fields, expected `#[debug(...)]` with one of {}",
+                    form, expected
+                ),
+            ));
+        }
+        if shorthand.is_some() {
+            return Err(syn::Error::new(
+                template.span(),
+                format!("duplicate `{}`, give the format string only once", form),
+            ));
+        }
+        shorthand = Some(template);
+    }
+    Ok((parse_attributes(&keyed)?, shorthand))
+}
+
+fn fields_attrs(fields: &Fields) -> Result<FieldsAndAttrs, syn::Error> {
+    let all: Vec<_> = fields.iter().collect();
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            let (mut attr, shorthand): (DebugFieldAttributes, _) = debug_attributes(&f.attrs)?;
+            match (shorthand, &attr.format) {
+                (Some(shorthand), Some(_)) => {
+                    return Err(syn::Error::new(
+                        shorthand.span(),
+                        "this format string conflicts with `format = \"...\"`, give the format string only once",
+                    ))
+                }
+                (Some(shorthand), None) => attr.format = Some(shorthand),
+                (None, _) => {}
+            }
+            if let Some(format) = &attr.format {
+                template::rewrite(format, &all, Some(i), &mut Vec::new())?;
+            }
+            if let (Some(_), Some(with)) = (&attr.format, &attr.with) {
+                return Err(syn::Error::new_spanned(
+                    with,
+                    "`with` cannot be combined with `format`",
+                ));
+            }
+            if let (Some(_), Some(_)) = (&attr.max_len, &attr.format) {
+                return Err(syn::Error::new(
+                    f.span(),
+                    "`max_len` cannot be combined with `format`",
+                ));
+            }
+            if let (Some(_), Some(with)) = (&attr.max_len, &attr.with) {
+                return Err(syn::Error::new_spanned(
+                    with,
+                    "`max_len` cannot be combined with `with`",
+                ));
+            }
+            if let (None, Some(_)) = (&f.ident, &attr.rename) {
+                return Err(syn::Error::new(
+                    f.span(),
+                    "`rename` has no effect on unnamed fields",
+                ));
+            }
+            if attr.flatten.is_some() {
+                if f.ident.is_none() {
+                    return Err(syn::Error::new(
+                        f.span(),
+                        "`flatten` is only supported on named fields",
+                    ));
+                }
+                let conflicts = [
+                    ("format", attr.format.is_some()),
+                    ("with", attr.with.is_some()),
+                    ("max_len", attr.max_len.is_some()),
+                    ("redact", attr.redact.is_some()),
+                    ("rename", attr.rename.is_some()),
+                    ("inline", attr.inline.is_some()),
+                ];
+                if let Some((conflict, _)) = conflicts.iter().find(|(_, set)| *set) {
+                    return Err(syn::Error::new(
+                        f.span(),
+                        format!("`flatten` cannot be combined with `{}`", conflict),
+                    ));
+                }
+            }
+            Ok((f.clone(), attr))
         })
+        .collect()
+}
+
+fn variant_arms(
+    variants: &syn::punctuated::Punctuated<Variant, syn::Token![,]>,
+    attrs: &DebugStructAttributes,
+    all_fields: &mut Vec<FieldsAndAttrs>,
+    visit_arms: &mut Vec<TokenStream>,
+) -> Result<Vec<TokenStream>, syn::Error> {
+    variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let (variant_attrs, _): (DebugVariantAttributes, _) = debug_attributes(&variant.attrs)?;
+            let fields_attrs = fields_attrs(&variant.fields)?;
+            all_fields.push(fields_attrs.clone());
+            let pattern = pattern(quote!(Self::#ident), &variant.fields);
+            let name = match (&variant_attrs.rename, attrs.rename_all) {
+                (Some(rename), _) => rename.clone(),
+                (None, Some(rule)) => rule.apply_to_variant(&ident.unraw().to_string()),
+                (None, None) => ident.unraw().to_string(),
+            };
+            let visits = method_calls(
+                &fields_attrs,
+                attrs,
+                variant_attrs.rename_all,
+                Target::Visitor,
+            );
+            visit_arms.push(quote!(#pattern => { #(#visits)* }));
+            let body = debug_fields(
+                &name,
+                &variant.fields,
+                &fields_attrs,
+                attrs,
+                variant_attrs.rename_all,
+                attrs.non_exhaustive.is_some() || variant_attrs.non_exhaustive.is_some(),
+            );
+            Ok(quote_spanned!(
+                variant.span() => #pattern => #body,
+            ))
+        })
+        .collect()
+}
+
+/// Name of the local variable a field is bound to when destructuring `self`.
+fn binding(index: usize, field: &Field) -> Ident {
+    match &field.ident {
+        Some(ident) => format_ident!("__self_{}", ident),
+        None => format_ident!("__self_{}", index),
+    }
+}
+
+fn pattern(path: TokenStream, fields: &Fields) -> TokenStream {
+    let bindings = fields.iter().enumerate().map(|(i, f)| binding(i, f));
+    match fields {
+        Fields::Named(named) => {
+            let members = named.named.iter().map(|f| &f.ident);
+            quote!(#path { #(#members: #bindings),* })
+        }
+        Fields::Unnamed(_) => quote!(#path ( #(#bindings),* )),
+        Fields::Unit => path,
+    }
+}
+
+/// Reports every name in `redact_all_except(...)` that matches no field, since
+/// a typo would otherwise silently redact the field it meant to keep. On an
+/// enum a name may match a field of any variant.
+fn check_redact_all_except(attrs: &[Attribute], data: &Data) -> syn::Result<()> {
+    let fields: Vec<Fields> = match data {
+        Data::Struct(DataStruct { fields, .. }) => vec![fields.clone()],
+        Data::Enum(DataEnum { variants, .. }) => {
+            variants.iter().map(|v| v.fields.clone()).collect()
+        }
+        Data::Union(DataUnion { fields, .. }) => vec![Fields::Named(fields.clone())],
+    };
+    let members: Vec<String> = fields
+        .iter()
+        .flat_map(|fields| fields.iter().enumerate().map(|(i, f)| member(i, f)))
         .collect();
-    generic_types.extend(attrs.bound.iter().map(|ty| {
-        let ty: syn::TypePath = syn::parse_str(ty).unwrap();
-        quote!(#ty: ::std::fmt::Debug)
-    }));
-    if let Some(where_clause) = &generics.where_clause {
-        generic_types.extend(
-            where_clause
-                .predicates
-                .iter()
-                .map(|p| quote_spanned!(p.span() => #p)),
-        );
+    let mut errors: Option<syn::Error> = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("debug")) {
+        // Malformed attributes are reported when they are parsed.
+        let _ = attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("redact_all_except") {
+                if meta.input.peek(Token![=]) {
+                    meta.value()?.parse::<Expr>()?;
+                } else if meta.input.peek(syn::token::Paren) {
+                    let _content;
+                    syn::parenthesized!(_content in meta.input);
+                }
+                return Ok(());
+            }
+            let content;
+            syn::parenthesized!(content in meta.input);
+            for name in Punctuated::<LitStr, Token![,]>::parse_terminated(&content)? {
+                if !members.contains(&name.value()) {
+                    let error = syn::Error::new(
+                        name.span(),
+                        format!("`redact_all_except` names no field `{}`", name.value()),
+                    );
+                    match &mut errors {
+                        Some(errors) => errors.combine(error),
+                        None => errors = Some(error),
+                    }
+                }
+            }
+            Ok(())
+        });
+    }
+    errors.map_or(Ok(()), Err)
+}
+
+/// Whether the input is `#[repr(packed)]` or `#[repr(packed(N))]`. Fields of
+/// such a type may be unaligned, so they are copied out instead of borrowed,
+/// which like the standard derive requires them to be `Copy`.
+fn is_packed(attrs: &[syn::Attribute]) -> bool {
+    let mut packed = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            packed |= meta.path.is_ident("packed");
+            if meta.input.peek(syn::token::Paren) {
+                let _content;
+                syn::parenthesized!(_content in meta.input);
+            }
+            Ok(())
+        });
+    }
+    packed
+}
+
+/// Rebinds the fields copied out of a packed struct as references, so the rest
+/// of the generated code can treat them like borrowed fields.
+fn locals(fields: &Fields, packed: bool) -> TokenStream {
+    if !packed {
+        return TokenStream::new();
+    }
+    let bindings = fields.iter().enumerate().map(|(i, f)| binding(i, f));
+    quote!(#(let #bindings = &#bindings;)*)
+}
+
+fn debug_fields(
+    name: &str,
+    fields: &Fields,
+    fields_attrs: &FieldsAndAttrs,
+    attrs: &DebugStructAttributes,
+    rename_all: Option<RenameRule>,
+    non_exhaustive: bool,
+) -> TokenStream {
+    let method_calls = method_calls(fields_attrs, attrs, rename_all, Target::Builder);
+    let finish = finish(non_exhaustive);
+    let fmt = local("fmt");
+    let debug_builder = local("debug_builder");
+    match fields {
+        Fields::Named(_) => quote!({
+            let #debug_builder = &mut #fmt.debug_struct(#name);
+            #(#method_calls)*
+            #debug_builder.#finish()
+        }),
+        Fields::Unnamed(_) => quote!({
+            let #debug_builder = &mut #fmt.debug_tuple(#name);
+            #(#method_calls)*
+            #debug_builder.#finish()
+        }),
+        Fields::Unit if non_exhaustive => quote!(#fmt.debug_struct(#name).finish_non_exhaustive()),
+        Fields::Unit => quote!(#fmt.write_str(#name)),
+    }
+}
+
+fn finish(non_exhaustive: bool) -> TokenStream {
+    if non_exhaustive {
+        quote!(finish_non_exhaustive)
+    } else {
+        quote!(finish)
+    }
+}
+
+fn generics(
+    generics: &Generics,
+    attrs: &DebugStructAttributes,
+    fields: &[FieldsAndAttrs],
+) -> Generics {
+    let predicates = match &attrs.bound {
+        Some(Bounds(bounds)) => bounds.clone(),
+        None => inferred_bounds(generics, attrs, fields),
+    };
+    let mut generics = generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
+}
+
+/// Bounds every type parameter, or associated type of one, that is printed
+/// through some field's `Debug` impl. Fields with their own `bound` attribute
+/// contribute those predicates instead.
+fn inferred_bounds(
+    generics: &Generics,
+    attrs: &DebugStructAttributes,
+    fields: &[FieldsAndAttrs],
+) -> Vec<WherePredicate> {
+    let params = type_params(generics);
+    let mut predicates = Vec::new();
+    let mut bounded = Vec::new();
+    for fields in fields {
+        let all: Vec<_> = fields.iter().map(|(f, _)| f).collect();
+        for (i, (f, attr)) in fields.iter().enumerate() {
+            if attr.skip.is_some() || attr.with.is_some() || redaction(i, f, attr, attrs).is_some()
+            {
+                continue;
+            }
+            match (&attr.bound, &attr.format) {
+                (Some(Bounds(bounds)), _) => predicates.extend(bounds.iter().cloned()),
+                (None, Some(format)) => {
+                    // The placeholders decide which traits the referenced
+                    // fields need, rather than `Debug`.
+                    let mut references = Vec::new();
+                    let _ = template::rewrite(format, &all, Some(i), &mut references);
+                    for reference in references {
+                        let fmt_trait = &reference.fmt_trait;
+                        predicates.extend(
+                            reference
+                                .bounded_types(&params)
+                                .iter()
+                                .filter(|ty| !attrs.unbound.contains(&quote!(#ty).to_string()))
+                                .map(|ty| parse_quote_spanned!(ty.span() => #ty: #fmt_trait)),
+                        );
+                    }
+                }
+                (None, None) => bounded_types(&f.ty, &params, &mut bounded),
+            }
+        }
+    }
+    predicates.extend(
+        bounded
+            .iter()
+            .filter(|ty| !attrs.unbound.contains(&quote!(#ty).to_string()))
+            .map(|ty| parse_quote_spanned!(ty.span() => #ty: ::std::fmt::Debug)),
+    );
+    predicates
+}
+
+/// Name of a field as written in the source, or its index for tuple fields.
+fn member(index: usize, field: &Field) -> String {
+    match &field.ident {
+        Some(ident) => ident.unraw().to_string(),
+        None => index.to_string(),
     }
-    let while_clause = quote!(#(#generic_types),*);
-    (impl_generics.clone(), impl_generics, while_clause)
 }
 
-fn method_calls(fields: &FieldsAndAttrs) -> Vec<TokenStream> {
+/// The placeholder printed instead of the field's value, if it is redacted.
+fn redaction<'a>(
+    index: usize,
+    field: &Field,
+    attr: &'a DebugFieldAttributes,
+    attrs: &'a DebugStructAttributes,
+) -> Option<&'a str> {
+    let placeholder = attrs.placeholder.as_deref().unwrap_or(REDACTED);
+    match &attr.redact {
+        Some(Redact(Some(placeholder))) => Some(placeholder),
+        Some(Redact(None)) => Some(placeholder),
+        None if !attrs.redact_all_except.is_empty()
+            && !attrs.redact_all_except.contains(&member(index, field)) =>
+        {
+            Some(placeholder)
+        }
+        None => None,
+    }
+}
+
+fn method_calls(
+    fields: &FieldsAndAttrs,
+    attrs: &DebugStructAttributes,
+    rename_all: Option<RenameRule>,
+    target: Target,
+) -> Vec<TokenStream> {
+    let all: Vec<_> = fields.iter().map(|(f, _)| f).collect();
     fields
         .iter()
-        .map(|(f, attr)| {
-            let name = &f.ident;
-            let value = match attr {
-                Ok(DebugFieldAttributes {
-                    format: Some(format),
-                    ..
-                }) => quote!(&format_args!(#format, &self.#name)),
-                _ => quote!(&self.#name),
-            };
-            quote_spanned!(
-                f.span() => debug_struct.field(stringify!(#name), #value);
-            )
+        .enumerate()
+        .filter(|(_, (_, attr))| attr.skip.is_none())
+        .map(|(i, (f, attr))| {
+            let call = method_call(&all, i, f, attr, attrs, rename_all, target);
+            match &attr.skip_if {
+                Some(skip_if) => {
+                    let binding = binding(i, f);
+                    quote!(
+                        if !#skip_if(#binding) {
+                            #call
+                        }
+                    )
+                }
+                None => call,
+            }
         })
         .collect()
 }
+
+fn method_call(
+    fields: &[&Field],
+    index: usize,
+    f: &Field,
+    attr: &DebugFieldAttributes,
+    attrs: &DebugStructAttributes,
+    rename_all: Option<RenameRule>,
+    target: Target,
+) -> TokenStream {
+    let binding = binding(index, f);
+    let debug_builder = local("debug_builder");
+    let visitor = local("visitor");
+    if attr.flatten.is_some() {
+        // Flattening goes through the `visit_fields` method the nested struct
+        // opts into, rather than a hidden method every struct would get. That
+        // method reports fields only, so a `non_exhaustive` on the nested
+        // struct has no effect here. A flattened field left out of
+        // `redact_all_except` keeps the nested names but hides their values.
+        return match (target, redaction(index, f, attr, attrs)) {
+            (Target::Builder, None) => quote_spanned!(
+                f.span() => #binding.visit_fields(&mut |name, value| {
+                    #debug_builder.field(name, value);
+                });
+            ),
+            (Target::Builder, Some(placeholder)) => quote_spanned!(
+                f.span() => #binding.visit_fields(&mut |name, _| {
+                    #debug_builder.field(name, &format_args!("{}", #placeholder));
+                });
+            ),
+            (Target::Visitor, None) => quote_spanned!(
+                f.span() => #binding.visit_fields(#visitor);
+            ),
+            (Target::Visitor, Some(placeholder)) => quote_spanned!(
+                f.span() => #binding.visit_fields(&mut |name, _| {
+                    #visitor(name, &format_args!("{}", #placeholder));
+                });
+            ),
+        };
+    }
+    let value = match (redaction(index, f, attr, attrs), attr) {
+        (Some(placeholder), _) => quote!(&format_args!("{}", #placeholder)),
+        (
+            None,
+            DebugFieldAttributes {
+                format: Some(format),
+                ..
+            },
+        ) => match template::rewrite(format, fields, Some(index), &mut Vec::new()) {
+            Ok(format) => quote!(&format_args!(#format)),
+            // A sibling field that is not bound, such as another field of a
+            // union.
+            Err(err) => err.to_compile_error(),
+        },
+        (
+            None,
+            DebugFieldAttributes {
+                with: Some(with), ..
+            },
+        ) => with_adapter(&binding, with),
+        (
+            None,
+            DebugFieldAttributes {
+                max_len: Some(max_len),
+                ..
+            },
+        ) => truncate_adapter(&binding, &f.ty, *max_len),
+        (None, DebugFieldAttributes { max_len: None, .. }) => match attrs.max_len {
+            Some(max_len) if is_collection(&f.ty) => truncate_adapter(&binding, &f.ty, max_len),
+            _ => quote!(#binding),
+        },
+    };
+    let value = match &attr.inline {
+        // The inner formatter does not inherit the `#` flag.
+        Some(()) => quote!(&format_args!("{:?}", #value)),
+        None => value,
+    };
+    let name = match (&attr.rename, rename_all) {
+        (Some(rename), _) => rename.clone(),
+        (None, Some(rule)) => rule.apply_to_field(&member(index, f)),
+        (None, None) => member(index, f),
+    };
+    match (target, &f.ident) {
+        (Target::Builder, Some(_)) => quote_spanned!(
+            f.span() => #debug_builder.field(#name, #value);
+        ),
+        (Target::Builder, None) => quote_spanned!(
+            f.span() => #debug_builder.field(#value);
+        ),
+        (Target::Visitor, _) => quote_spanned!(
+            f.span() => #visitor(#name, #value);
+        ),
+    }
+}
+
+/// Wraps a field in a value whose `Debug` impl forwards to the user-provided
+/// `fn(&T, &mut Formatter) -> fmt::Result`.
+fn with_adapter(binding: &Ident, with: &syn::Path) -> TokenStream {
+    quote!(&{
+        struct DebugWith<'a, T: ?::std::marker::Sized>(
+            &'a T,
+            fn(&T, &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result,
+        );
+        impl<T: ?::std::marker::Sized> ::std::fmt::Debug for DebugWith<'_, T> {
+            fn fmt(&self, fmt: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                (self.1)(self.0, fmt)
+            }
+        }
+        DebugWith(#binding, #with)
+    })
+}
+
+/// Whether a field type looks like a string or a sequence, which decides if the
+/// container-level `max_len` applies to it.
+fn is_collection(ty: &Type) -> bool {
+    const COLLECTIONS: &[&str] = &[
+        "String",
+        "str",
+        "Vec",
+        "VecDeque",
+        "LinkedList",
+        "HashSet",
+        "BTreeSet",
+        "BinaryHeap",
+        "HashMap",
+        "BTreeMap",
+    ];
+    match ty {
+        Type::Array(_) | Type::Slice(_) => true,
+        Type::Reference(reference) => is_collection(&reference.elem),
+        Type::Group(group) => is_collection(&group.elem),
+        Type::Paren(paren) => is_collection(&paren.elem),
+        Type::Path(TypePath { qself: None, path }) => path
+            .segments
+            .last()
+            .is_some_and(|segment| COLLECTIONS.iter().any(|name| segment.ident == name)),
+        _ => false,
+    }
+}
+
+/// Number of references around a field type, e.g. 1 for `&'a [T]`.
+fn reference_depth(ty: &Type) -> usize {
+    match ty {
+        Type::Reference(reference) => 1 + reference_depth(&reference.elem),
+        Type::Group(group) => reference_depth(&group.elem),
+        Type::Paren(paren) => reference_depth(&paren.elem),
+        _ => 0,
+    }
+}
+
+/// Wraps a field in a value whose `Debug` impl prints at most `max_len`
+/// characters of a string, or `max_len` entries of a map or elements of
+/// anything else that can be iterated by reference, followed by the total
+/// length.
+///
+/// Strings, maps and sequences are told apart by autoref-based specialization
+/// at the call site, where the field type is known: the string and map impls
+/// are found without taking a reference to the `Truncate` wrapper, the sequence
+/// impl only after taking one. The chosen impl hands back the function doing the
+/// formatting.
+///
+/// A field of type `&[T]` or `&Vec<T>` is reborrowed down to the collection
+/// first, since `&&[T]` is not `IntoIterator`.
+fn truncate_adapter(binding: &Ident, ty: &Type, max_len: usize) -> TokenStream {
+    let derefs = (0..reference_depth(ty)).map(|_| quote!(*));
+    quote!(&{
+        let value = &*#(#derefs)*#binding;
+        type TruncateFn<'a, T> =
+            fn(&'a T, usize, &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result;
+        struct Truncate<'a, T: ?::std::marker::Sized>(&'a T);
+        trait TruncateStr<'a, T: ?::std::marker::Sized> {
+            fn truncate_fn(&self) -> TruncateFn<'a, T>;
+        }
+        impl<'a, T> TruncateStr<'a, T> for Truncate<'a, T>
+        where
+            T: ?::std::marker::Sized + ::std::convert::AsRef<str>,
+        {
+            fn truncate_fn(&self) -> TruncateFn<'a, T> {
+                |value, max_len, fmt| {
+                    let string = value.as_ref();
+                    match string.char_indices().nth(max_len) {
+                        ::std::option::Option::Some((end, _)) => {
+                            ::std::fmt::Debug::fmt(&string[..end], fmt)?;
+                            ::std::write!(fmt, "... ({} chars)", string.chars().count())
+                        }
+                        ::std::option::Option::None => ::std::fmt::Debug::fmt(string, fmt),
+                    }
+                }
+            }
+        }
+        trait TruncateIter<'a, T: ?::std::marker::Sized> {
+            fn truncate_fn(&self) -> TruncateFn<'a, T>;
+        }
+        impl<'a, T> TruncateIter<'a, T> for &Truncate<'a, T>
+        where
+            T: ?::std::marker::Sized,
+            &'a T: ::std::iter::IntoIterator,
+            <&'a T as ::std::iter::IntoIterator>::Item: ::std::fmt::Debug,
+        {
+            fn truncate_fn(&self) -> TruncateFn<'a, T> {
+                |value, max_len, fmt| {
+                    let mut iter = value.into_iter();
+                    let mut list = fmt.debug_list();
+                    list.entries(iter.by_ref().take(max_len));
+                    let rest = iter.count();
+                    if rest > 0 {
+                        list.entry(&::std::format_args!("... ({} items)", max_len + rest));
+                    }
+                    list.finish()
+                }
+            }
+        }
+        trait MapEntry {
+            fn key(&self) -> &dyn ::std::fmt::Debug;
+            fn value(&self) -> &dyn ::std::fmt::Debug;
+        }
+        impl<K: ::std::fmt::Debug, V: ::std::fmt::Debug> MapEntry for (&K, &V) {
+            fn key(&self) -> &dyn ::std::fmt::Debug {
+                self.0
+            }
+            fn value(&self) -> &dyn ::std::fmt::Debug {
+                self.1
+            }
+        }
+        trait TruncateMap<'a, T: ?::std::marker::Sized> {
+            fn truncate_fn(&self) -> TruncateFn<'a, T>;
+        }
+        impl<'a, T> TruncateMap<'a, T> for Truncate<'a, T>
+        where
+            T: ?::std::marker::Sized,
+            &'a T: ::std::iter::IntoIterator,
+            <&'a T as ::std::iter::IntoIterator>::Item: MapEntry,
+        {
+            fn truncate_fn(&self) -> TruncateFn<'a, T> {
+                |value, max_len, fmt| {
+                    let mut iter = value.into_iter();
+                    let mut map = fmt.debug_map();
+                    for entry in iter.by_ref().take(max_len) {
+                        map.entry(entry.key(), entry.value());
+                    }
+                    let rest = iter.count();
+                    if rest > 0 {
+                        map.entry(
+                            &::std::format_args!("..."),
+                            &::std::format_args!("({} entries)", max_len + rest),
+                        );
+                    }
+                    map.finish()
+                }
+            }
+        }
+        struct Truncated<'a, T: ?::std::marker::Sized>(&'a T, usize, TruncateFn<'a, T>);
+        impl<T: ?::std::marker::Sized> ::std::fmt::Debug for Truncated<'_, T> {
+            fn fmt(&self, fmt: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                (self.2)(self.0, self.1, fmt)
+            }
+        }
+        Truncated(value, #max_len, (&Truncate(value)).truncate_fn())
+    })
+}
@@ -0,0 +1,93 @@
+use deluxe::{ParseMetaItem, ParseMode};
+use syn::{parse::ParseStream, LitStr};
+
+/// Case convention applied by `#[debug(rename_all = "...")]`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+const RULES: &[(&str, RenameRule)] = &[
+    ("lowercase", RenameRule::Lower),
+    ("UPPERCASE", RenameRule::Upper),
+    ("PascalCase", RenameRule::Pascal),
+    ("camelCase", RenameRule::Camel),
+    ("snake_case", RenameRule::Snake),
+    ("SCREAMING_SNAKE_CASE", RenameRule::ScreamingSnake),
+    ("kebab-case", RenameRule::Kebab),
+    ("SCREAMING-KEBAB-CASE", RenameRule::ScreamingKebab),
+];
+
+impl RenameRule {
+    /// Renames a field, which is expected to be written in snake_case.
+    pub(crate) fn apply_to_field(self, field: &str) -> String {
+        self.apply(field.split('_').filter(|word| !word.is_empty()))
+    }
+
+    /// Renames a variant or a type, which is expected to be written in
+    /// PascalCase.
+    pub(crate) fn apply_to_variant(self, variant: &str) -> String {
+        let mut words = Vec::new();
+        let mut start = 0;
+        for (i, ch) in variant.char_indices().skip(1) {
+            if ch.is_uppercase() {
+                words.push(&variant[start..i]);
+                start = i;
+            }
+        }
+        words.push(&variant[start..]);
+        self.apply(words.into_iter())
+    }
+
+    fn apply<'a>(self, words: impl Iterator<Item = &'a str>) -> String {
+        let words = words.map(str::to_lowercase);
+        match self {
+            RenameRule::Lower => words.collect(),
+            RenameRule::Upper => words.map(|word| word.to_uppercase()).collect(),
+            RenameRule::Pascal => words.map(|word| capitalize(&word)).collect(),
+            RenameRule::Camel => words
+                .enumerate()
+                .map(|(i, word)| if i == 0 { word } else { capitalize(&word) })
+                .collect(),
+            RenameRule::Snake => words.collect::<Vec<_>>().join("_"),
+            RenameRule::ScreamingSnake => words.collect::<Vec<_>>().join("_").to_uppercase(),
+            RenameRule::Kebab => words.collect::<Vec<_>>().join("-"),
+            RenameRule::ScreamingKebab => words.collect::<Vec<_>>().join("-").to_uppercase(),
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+impl ParseMetaItem for RenameRule {
+    fn parse_meta_item(input: ParseStream, _mode: ParseMode) -> deluxe::Result<Self> {
+        let lit: LitStr = input.parse()?;
+        let value = lit.value();
+        match RULES.iter().find(|(name, _)| *name == value) {
+            Some((_, rule)) => Ok(*rule),
+            None => {
+                let names: Vec<_> = RULES
+                    .iter()
+                    .map(|(name, _)| format!("`{}`", name))
+                    .collect();
+                Err(syn::Error::new(
+                    lit.span(),
+                    format!("unknown rename rule, expected one of {}", names.join(", ")),
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use std::fmt::Display;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Field, Ident, LitStr, Type};
+
+use crate::{binding, bound::bounded_types, member};
+
+/// A field referenced by a template, along with the formatting trait its
+/// placeholder uses.
+pub(crate) struct Reference<'a> {
+    pub(crate) field: &'a Field,
+    pub(crate) fmt_trait: TokenStream,
+    /// Whether the placeholder is `{:p}`.
+    pointer: bool,
+}
+
+impl Reference<'_> {
+    /// The types that need `fmt_trait` for the placeholder to compile.
+    ///
+    /// `Pointer` is implemented by `&T` and `*const T` whatever `T` is, so for
+    /// `{:p}` the field type as a whole is bounded rather than the type
+    /// parameters inside it.
+    pub(crate) fn bounded_types(&self, params: &[&Ident]) -> Vec<Type> {
+        let mut bounded = Vec::new();
+        bounded_types(&self.field.ty, params, &mut bounded);
+        if self.pointer && !bounded.is_empty() {
+            return vec![self.field.ty.clone()];
+        }
+        bounded
+    }
+}
+
+/// Rewrites every `{field}` placeholder of a template to refer to the local
+/// binding of that field instead, so that the fields are picked up as
+/// implicitly captured arguments. The result is the arguments of a
+/// `format_args!`.
+///
+/// The bindings are references, whose `Pointer` impl would print their own
+/// address, so `{field:p}` placeholders get a named argument dereferencing the
+/// binding instead.
+///
+/// Fields are referred to by name or tuple index, optionally prefixed with
+/// `self.`. When the template formats a single field, given by `current`, the
+/// positional `{}` and `{0}` refer to that field.
+pub(crate) fn rewrite<'a>(
+    template: &LitStr,
+    fields: &[&'a Field],
+    current: Option<usize>,
+    references: &mut Vec<Reference<'a>>,
+) -> syn::Result<TokenStream> {
+    let value = template.value();
+    let mut rewritten = String::new();
+    let mut pointers = Vec::new();
+    let mut chars = value.char_indices().peekable();
+    while let Some((start, ch)) = chars.next() {
+        match ch {
+            '{' if matches!(chars.peek(), Some((_, '{'))) => {
+                chars.next();
+                rewritten.push_str("{{");
+            }
+            '}' if matches!(chars.peek(), Some((_, '}'))) => {
+                chars.next();
+                rewritten.push_str("}}");
+            }
+            '}' => {
+                // Quote the text since the last placeholder, which is where
+                // the opening brace is missing.
+                let text = value[..=start].rsplit('}').nth(1).unwrap_or_default();
+                let text = format!("{}}}", text);
+                return Err(syn::Error::new(
+                    span(template, &text),
+                    format!(
+                        "unmatched `}}` in `{}`, write `}}}}` for a literal brace",
+                        text
+                    ),
+                ));
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '}')) => break,
+                        Some((_, ch)) => placeholder.push(ch),
+                        None => {
+                            let text = &value[start..];
+                            return Err(syn::Error::new(
+                                span(template, text),
+                                format!(
+                                    "unterminated placeholder `{}`, close it with `}}` or write \
+                                     `{{{{` for a literal brace",
+                                    text
+                                ),
+                            ));
+                        }
+                    }
+                }
+                let placeholder = Placeholder {
+                    template,
+                    text: format!("{{{}}}", placeholder),
+                };
+                let (name, spec) = match placeholder.inner().split_once(':') {
+                    Some((name, spec)) => (name.trim(), Some(spec)),
+                    None => (placeholder.inner().trim(), None),
+                };
+                let (index, field) = lookup(&placeholder, fields, current, name)?;
+                let spec_type = spec.unwrap_or("").trim_end();
+                let pointer = spec_type.ends_with('p');
+                references.push(Reference {
+                    field,
+                    fmt_trait: fmt_trait(spec_type),
+                    pointer,
+                });
+                let binding = binding(index, field);
+                rewritten.push('{');
+                if pointer {
+                    let name = format_ident!("__pointer_{}", member(index, field));
+                    rewritten.push_str(&name.to_string());
+                    if !pointers.iter().any(|(other, _)| *other == name) {
+                        pointers.push((name, binding));
+                    }
+                } else {
+                    rewritten.push_str(&binding.to_string());
+                }
+                if let Some(spec) = spec {
+                    rewritten.push(':');
+                    rewritten.push_str(&rewrite_spec(&placeholder, fields, current, spec)?);
+                }
+                rewritten.push('}');
+            }
+            ch => rewritten.push(ch),
+        }
+    }
+    let rewritten = LitStr::new(&rewritten, template.span());
+    let pointers = pointers
+        .iter()
+        .map(|(name, binding)| quote!(, #name = *#binding));
+    Ok(quote!(#rewritten #(#pointers)*))
+}
+
+/// A `{...}` placeholder of a template, which errors quote and point at.
+struct Placeholder<'a> {
+    template: &'a LitStr,
+    text: String,
+}
+
+impl Placeholder<'_> {
+    fn inner(&self) -> &str {
+        &self.text[1..self.text.len() - 1]
+    }
+
+    fn error(&self, message: impl Display) -> syn::Error {
+        syn::Error::new(span(self.template, &self.text), message)
+    }
+}
+
+/// The span of `text` within the template, where the compiler can point into a
+/// string literal, or of the whole template otherwise.
+fn span(template: &LitStr, text: &str) -> Span {
+    let token = template.token();
+    token
+        .to_string()
+        .find(text)
+        .and_then(|start| token.subspan(start..start + text.len()))
+        .unwrap_or_else(|| template.span())
+}
+
+fn lookup<'a>(
+    placeholder: &Placeholder,
+    fields: &[&'a Field],
+    current: Option<usize>,
+    name: &str,
+) -> syn::Result<(usize, &'a Field)> {
+    if let Some(current) = current {
+        if name.is_empty() || name == "0" {
+            return Ok((current, fields[current]));
+        }
+        if name.chars().all(|ch| ch.is_ascii_digit()) {
+            return Err(placeholder.error(format_args!(
+                "invalid placeholder `{}`, only `{{}}` and `{{0}}` refer to the field itself, \
+                 use `{{self.{}}}` for other fields",
+                placeholder.text, name
+            )));
+        }
+    }
+    if name.is_empty() {
+        return Err(placeholder.error(format_args!(
+            "positional placeholder `{}` is not supported, refer to fields by name",
+            placeholder.text
+        )));
+    }
+    let member_name = name.strip_prefix("self.").unwrap_or(name);
+    fields
+        .iter()
+        .enumerate()
+        .find(|(i, field)| member(*i, field) == member_name)
+        .map(|(i, field)| (i, *field))
+        .ok_or_else(|| {
+            placeholder.error(format_args!(
+                "no field named `{}` in placeholder `{}`",
+                member_name, placeholder.text
+            ))
+        })
+}
+
+/// Rewrites `name$` width and precision arguments that refer to fields.
+fn rewrite_spec(
+    placeholder: &Placeholder,
+    fields: &[&Field],
+    current: Option<usize>,
+    spec: &str,
+) -> syn::Result<String> {
+    let mut rewritten = String::new();
+    let mut word = String::new();
+    for ch in spec.chars() {
+        if ch.is_alphanumeric() || ch == '_' || ch == '.' && word == "self" {
+            word.push(ch);
+            continue;
+        }
+        if ch == '$' && !word.is_empty() {
+            let (index, field) = lookup(placeholder, fields, current, &word)?;
+            word = binding(index, field).to_string();
+        }
+        rewritten.push_str(&word);
+        rewritten.push(ch);
+        word.clear();
+    }
+    rewritten.push_str(&word);
+    Ok(rewritten)
+}
+
+/// The formatting trait selected by the type at the end of a format spec.
+pub(crate) fn fmt_trait(spec: &str) -> TokenStream {
+    if spec.ends_with('?') {
+        return quote!(::std::fmt::Debug);
+    }
+    match spec.chars().last() {
+        Some('x') => quote!(::std::fmt::LowerHex),
+        Some('X') => quote!(::std::fmt::UpperHex),
+        Some('o') => quote!(::std::fmt::Octal),
+        Some('b') => quote!(::std::fmt::Binary),
+        Some('e') => quote!(::std::fmt::LowerExp),
+        Some('E') => quote!(::std::fmt::UpperExp),
+        Some('p') => quote!(::std::fmt::Pointer),
+        _ => quote!(::std::fmt::Display),
+    }
+}
@@ -0,0 +1,59 @@
+// Enums are formatted variant by variant, the same way the standard library's
+// derive does it: struct-like variants through Formatter::debug_struct,
+// tuple-like variants through Formatter::debug_tuple and unit variants by
+// writing their name.
+//
+// The generated impl matches on self and binds every field of the variant, so
+// the #[debug(format = "...")] attribute works on fields of any variant kind.
+//
+//     impl Debug for Instruction {
+//         fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+//             match self {
+//                 Self::Nop => fmt.write_str("Nop"),
+//                 Self::Load(__self_0, __self_1) => { ... }
+//                 Self::Jump { target: __self_target } => { ... }
+//             }
+//         }
+//     }
+//
+//
+// Resources:
+//
+//   - The DebugTuple helper for formatting tuple structs and variants:
+//     https://doc.rust-lang.org/std/fmt/struct.DebugTuple.html
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub enum Instruction<T> {
+    Nop,
+    Load(u8, #[debug(format = "{:#04x}")] u8),
+    Jump {
+        #[debug(format = "@{}")]
+        target: u16,
+        label: T,
+    },
+}
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+fn main() {
+    let nop: Instruction<&str> = Instruction::Nop;
+    assert_eq!(format!("{:?}", nop), "Nop");
+
+    let load: Instruction<&str> = Instruction::Load(3, 0x1f);
+    assert_eq!(format!("{:?}", load), "Load(3, 0x1f)");
+
+    let jump = Instruction::Jump {
+        target: 512,
+        label: "loop",
+    };
+    assert_eq!(
+        format!("{:?}", jump),
+        r#"Jump { target: @512, label: "loop" }"#,
+    );
+
+    let expected = "Jump {\n    target: @512,\n    label: \"loop\",\n}";
+    assert_eq!(format!("{:#?}", jump), expected);
+}
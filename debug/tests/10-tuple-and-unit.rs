@@ -0,0 +1,20 @@
+// Tuple structs are formatted with Formatter::debug_tuple and unit structs by
+// writing their name, matching the output of the standard library's derive.
+// Format attributes apply to unnamed fields as well.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Meters(#[debug(format = "{}m")] u32);
+
+#[derive(CustomDebug)]
+pub struct Pair<T>(T, #[debug(format = "0b{:04b}")] u8);
+
+#[derive(CustomDebug)]
+pub struct Marker;
+
+fn main() {
+    assert_eq!(format!("{:?}", Meters(12)), "Meters(12m)");
+    assert_eq!(format!("{:?}", Pair('x', 5)), "Pair('x', 0b0101)");
+    assert_eq!(format!("{:?}", Marker), "Marker");
+}
@@ -0,0 +1,75 @@
+// Fields can be left out of the output entirely with #[debug(skip)], or have
+// their value replaced by a placeholder with #[debug(redact)] while the field
+// name is still printed. The placeholder defaults to <redacted> and can be
+// changed per field with #[debug(redact = "...")], or for every field of the
+// container with #[debug(placeholder = "...")].
+//
+// Credential types usually want the opposite default: with
+// #[debug(redact_all_except("..."))] on the container, every field not listed
+// is redacted.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Login {
+    user: &'static str,
+    #[debug(redact)]
+    password: &'static str,
+    #[debug(redact = "***")]
+    otp: u32,
+    #[debug(skip)]
+    attempts: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug(redact_all_except("id", "scopes"), placeholder = "<hidden>")]
+pub struct Token {
+    id: u64,
+    secret: &'static str,
+    refresh: &'static str,
+    scopes: Vec<&'static str>,
+}
+
+#[derive(CustomDebug)]
+#[debug(redact_all_except("0"))]
+pub enum Credential {
+    Basic(&'static str, &'static str),
+    Bearer {
+        token: &'static str,
+        #[debug(skip)]
+        issued: u64,
+    },
+}
+
+fn main() {
+    let login = Login {
+        user: "ferris",
+        password: "hunter2",
+        otp: 123456,
+        attempts: 3,
+    };
+    assert_eq!(
+        format!("{:?}", login),
+        r#"Login { user: "ferris", password: <redacted>, otp: *** }"#,
+    );
+
+    let token = Token {
+        id: 7,
+        secret: "s3cr3t",
+        refresh: "r3fr3sh",
+        scopes: vec!["read"],
+    };
+    assert_eq!(
+        format!("{:?}", token),
+        r#"Token { id: 7, secret: <hidden>, refresh: <hidden>, scopes: ["read"] }"#,
+    );
+
+    let basic = Credential::Basic("ferris", "hunter2");
+    assert_eq!(format!("{:?}", basic), r#"Basic("ferris", <redacted>)"#);
+
+    let bearer = Credential::Bearer {
+        token: "abc",
+        issued: 0,
+    };
+    assert_eq!(format!("{:?}", bearer), "Bearer { token: <redacted> }");
+}
@@ -0,0 +1,77 @@
+// For types whose Debug output is unhelpful, like byte buffers or durations, a
+// field can name a formatting function with #[debug(with = path)]. The function
+// has the signature fn(&T, &mut fmt::Formatter) -> fmt::Result, where T is the
+// type of the field, and may be generic. The path can be written bare or as a
+// string literal. A function imported under a name the generated code also
+// uses, such as fmt, is still the one called.
+//
+// The macro wraps the field in a small adapter type implementing Debug by
+// calling the function, so no newtype wrapper is needed in the caller's code.
+
+use std::fmt;
+use std::time::Duration;
+
+use derive_debug::CustomDebug;
+
+mod hex {
+    use std::fmt;
+
+    pub fn fmt<T: AsRef<[u8]>>(bytes: &T, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in bytes.as_ref() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+fn millis(duration: &Duration, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}ms", duration.as_millis())
+}
+
+#[derive(CustomDebug)]
+pub struct Packet {
+    #[debug(with = "hex::fmt")]
+    payload: Vec<u8>,
+    #[debug(with = millis)]
+    latency: Duration,
+}
+
+#[derive(CustomDebug)]
+pub struct Digest(#[debug(with = hex::fmt)] [u8; 4]);
+
+mod imported {
+    use derive_debug::CustomDebug;
+
+    use super::hex::fmt;
+
+    fn visitor(bytes: &[u8; 2]) -> bool {
+        bytes.is_empty()
+    }
+
+    fn debug_builder(bytes: &[u8; 2]) -> bool {
+        bytes == &[0, 0]
+    }
+
+    #[derive(CustomDebug)]
+    pub struct Checksum(
+        #[debug(with = fmt, skip_if = visitor)] pub [u8; 2],
+        #[debug(with = fmt, skip_if = debug_builder)] pub [u8; 2],
+    );
+}
+
+fn main() {
+    let packet = Packet {
+        payload: vec![0xde, 0xad, 0xbe, 0xef],
+        latency: Duration::from_millis(42),
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        "Packet { payload: deadbeef, latency: 42ms }",
+    );
+
+    let digest = Digest([1, 2, 3, 255]);
+    assert_eq!(format!("{:?}", digest), "Digest(010203ff)");
+
+    let checksum = imported::Checksum([0xab, 0xcd], [0, 0]);
+    assert_eq!(format!("{:?}", checksum), "Checksum(abcd)");
+}
@@ -0,0 +1,57 @@
+// Log processing tooling often expects specific key names. Fields can be
+// renamed with #[debug(rename = "...")] and the printed type name can be
+// changed the same way on the struct itself.
+//
+// A container-level #[debug(rename_all = "...")] converts every name at once
+// using one of the usual case conventions: on a struct it applies to the
+// fields, on an enum to the variant names. Placing it on a variant converts
+// the fields of that variant. An explicit rename always takes precedence.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(rename = "request", rename_all = "camelCase")]
+pub struct HttpRequest {
+    request_id: u32,
+    #[debug(rename = "URL")]
+    target_url: &'static str,
+    r#type: &'static str,
+}
+
+#[derive(CustomDebug)]
+#[debug(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Event {
+    ConnectionOpened,
+    #[debug(rename_all = "kebab-case")]
+    BytesReceived {
+        byte_count: usize,
+        remote_port: u16,
+    },
+    #[debug(rename = "closed")]
+    ConnectionClosed(u8),
+}
+
+fn main() {
+    let request = HttpRequest {
+        request_id: 3,
+        target_url: "/",
+        r#type: "GET",
+    };
+    assert_eq!(
+        format!("{:?}", request),
+        r#"request { requestId: 3, URL: "/", type: "GET" }"#,
+    );
+
+    assert_eq!(format!("{:?}", Event::ConnectionOpened), "CONNECTION_OPENED");
+
+    let received = Event::BytesReceived {
+        byte_count: 512,
+        remote_port: 8080,
+    };
+    assert_eq!(
+        format!("{:?}", received),
+        "BYTES_RECEIVED { byte-count: 512, remote-port: 8080 }",
+    );
+
+    assert_eq!(format!("{:?}", Event::ConnectionClosed(0)), "closed(0)");
+}
@@ -0,0 +1,71 @@
+// Printing a field holding thousands of elements floods the logs. With
+// #[debug(max_len = N)] a string field prints only its first N characters and
+// a collection field only its first N elements, followed by the total length.
+// A map field prints its first N entries as a map.
+//
+// A #[debug(max_len = N)] on the container sets the default for every field
+// whose type looks like a string, a sequence or a map, i.e. String, str, Vec and
+// the other standard collections, arrays and slices. Fields of other types are
+// not affected; fields with their own max_len keep it. Borrowed collections
+// such as &[T] and &Vec<T> are truncated like the collections themselves.
+
+use std::collections::BTreeMap;
+
+use derive_debug::CustomDebug;
+
+type Samples = Vec<u32>;
+
+#[derive(CustomDebug)]
+pub struct Capture<'a> {
+    #[debug(max_len = 3)]
+    samples: Samples,
+    #[debug(max_len = 5)]
+    label: String,
+    #[debug(max_len = 5)]
+    short: &'static str,
+    #[debug(max_len = 1)]
+    units: BTreeMap<&'static str, &'static str>,
+    #[debug(max_len = 2)]
+    history: &'a Vec<u32>,
+}
+
+#[derive(CustomDebug)]
+#[debug(max_len = 2)]
+pub struct Batch {
+    id: u32,
+    items: Vec<char>,
+    name: &'static str,
+    #[debug(max_len = 4)]
+    tags: [u8; 6],
+    parent: Option<u32>,
+    counts: BTreeMap<char, u32>,
+    bytes: &'static [u8],
+}
+
+fn main() {
+    let capture = Capture {
+        samples: (0..10_000).collect(),
+        label: "temperature".to_owned(),
+        short: "hi",
+        units: BTreeMap::from([("rate", "Hz"), ("temperature", "C")]),
+        history: &vec![7, 8, 9],
+    };
+    assert_eq!(
+        format!("{:?}", capture),
+        r#"Capture { samples: [0, 1, 2, ... (10000 items)], label: "tempe"... (11 chars), short: "hi", units: {"rate": "Hz", ...: (2 entries)}, history: [7, 8, ... (3 items)] }"#,
+    );
+
+    let batch = Batch {
+        id: 1,
+        items: vec!['a', 'b'],
+        name: "nightly",
+        tags: [1, 2, 3, 4, 5, 6],
+        parent: Some(0),
+        counts: BTreeMap::from([('a', 1), ('b', 2), ('c', 3)]),
+        bytes: b"xyz",
+    };
+    assert_eq!(
+        format!("{:?}", batch),
+        r#"Batch { id: 1, items: ['a', 'b'], name: "ni"... (7 chars), tags: [1, 2, 3, 4, ... (6 items)], parent: Some(0), counts: {'a': 1, 'b': 2, ...: (3 entries)}, bytes: [120, 121, ... (3 items)] }"#,
+    );
+}
@@ -0,0 +1,69 @@
+// With #[debug(non_exhaustive)] on the container, or on a single variant, the
+// generated impl ends with finish_non_exhaustive() instead of finish(), which
+// prints `..` after the fields to tell the reader that some were left out.
+//
+// Fields can also be omitted conditionally at runtime: #[debug(skip_if = path)]
+// names a function fn(&T) -> bool, and the field is only printed when it
+// returns false. Option::is_none keeps Option-heavy structs compact.
+
+use derive_debug::CustomDebug;
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+#[derive(CustomDebug)]
+#[debug(non_exhaustive)]
+pub struct Config {
+    name: &'static str,
+    #[debug(skip)]
+    internal: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct Options {
+    #[debug(skip_if = "Option::is_none")]
+    timeout: Option<u32>,
+    #[debug(skip_if = Option::is_none)]
+    retries: Option<u32>,
+    #[debug(skip_if = is_zero)]
+    flags: u32,
+}
+
+#[derive(CustomDebug)]
+pub enum Shape {
+    #[debug(non_exhaustive)]
+    Circle(u32),
+    Square(u32),
+    #[debug(non_exhaustive)]
+    Empty,
+}
+
+fn main() {
+    let config = Config {
+        name: "prod",
+        internal: 0,
+    };
+    assert_eq!(format!("{:?}", config), r#"Config { name: "prod", .. }"#);
+
+    let options = Options {
+        timeout: Some(30),
+        retries: None,
+        flags: 0,
+    };
+    assert_eq!(format!("{:?}", options), "Options { timeout: Some(30) }");
+
+    let options = Options {
+        timeout: None,
+        retries: Some(3),
+        flags: 1,
+    };
+    assert_eq!(
+        format!("{:?}", options),
+        "Options { retries: Some(3), flags: 1 }",
+    );
+
+    assert_eq!(format!("{:?}", Shape::Circle(1)), "Circle(1, ..)");
+    assert_eq!(format!("{:?}", Shape::Square(2)), "Square(2)");
+    assert_eq!(format!("{:?}", Shape::Empty), "Empty { .. }");
+}
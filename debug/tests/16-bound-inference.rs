@@ -0,0 +1,79 @@
+// Bounds are inferred from the field types instead of being put on every type
+// parameter. A parameter that is only ever mentioned inside PhantomData gets no
+// bound, an associated type of a parameter such as T::Value is bounded by
+// itself without bounding T, and fields that are skipped, redacted or printed
+// through a `with` function do not contribute any bound at all.
+//
+// When inference is not enough, #[debug(bound = "...")] takes full
+// where-predicates. On the container it replaces inference entirely, on a
+// field it replaces only the bounds that field would have contributed.
+//
+//     impl<T, U, K> Debug for Inferred<T, U, K>
+//     where
+//         T::Value: Debug,
+//         U: Debug,
+//         T: Trait,
+//     {...}
+
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
+
+use derive_debug::CustomDebug;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Inferred<T, U, K>
+where
+    T: Trait,
+{
+    values: Vec<T::Value>,
+    pair: Option<(u8, U)>,
+    marker: PhantomData<(T, K)>,
+    #[debug(skip)]
+    key: Option<K>,
+}
+
+#[derive(CustomDebug)]
+pub struct PerField<T, U> {
+    #[debug(bound = "T: Clone + Debug")]
+    first: T,
+    second: U,
+}
+
+fn opaque<T>(_: &T, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("?")
+}
+
+#[derive(CustomDebug)]
+#[debug(bound = "Vec<T>: Debug")]
+pub struct Container<T, U> {
+    items: Vec<T>,
+    #[debug(with = opaque)]
+    extra: U,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+
+    impl Trait for NotDebug {
+        type Value = u8;
+    }
+
+    assert_debug::<Inferred<NotDebug, u8, NotDebug>>();
+    assert_debug::<PerField<u8, &str>>();
+    assert_debug::<Container<u8, NotDebug>>();
+
+    let container = Container {
+        items: vec![1, 2],
+        extra: NotDebug,
+    };
+    assert_eq!(
+        format!("{:?}", container),
+        "Container { items: [1, 2], extra: ? }",
+    );
+}
@@ -0,0 +1,12 @@
+// A `bound` attribute that parses neither as where-predicates nor as a type is
+// reported at the attribute rather than making the macro panic.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(bound = "T Debug")]
+pub struct Wrapper<T> {
+    value: T,
+}
+
+fn main() {}
@@ -0,0 +1,60 @@
+// The generated impl splits the input's generics the way Generics::split_for_impl
+// does: the impl header repeats the parameters with their bounds but without
+// default values, the type position lists only the parameter names, and the
+// inferred bounds are merged into any existing where-clause.
+//
+//     impl<'a, T: ?Sized + Display, U, const N: usize> Debug for Generic<'a, T, U, N>
+//     where
+//         U: Clone,
+//         T: Debug,
+//         U: Debug,
+//     {...}
+//
+// Inline bounds, lifetimes, default type parameters and const generics with
+// defaults must all survive this round trip.
+
+use std::fmt::{Debug, Display};
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Generic<'a, T: ?Sized + Display, U = u8, const N: usize = 4>
+where
+    U: Clone,
+{
+    name: &'a T,
+    values: [U; N],
+}
+
+#[derive(CustomDebug)]
+pub struct Borrowed<'a, 'b: 'a, T: 'b> {
+    short: &'a str,
+    long: &'b [T],
+}
+
+#[derive(CustomDebug)]
+pub enum Either<L, R = L> {
+    Left(L),
+    Right(R),
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_debug::<Generic<str>>();
+    assert_debug::<Generic<str, char, 2>>();
+    assert_debug::<Borrowed<u8>>();
+    assert_debug::<Either<u8>>();
+
+    let generic: Generic<str> = Generic {
+        name: "bytes",
+        values: [1, 2, 3, 4],
+    };
+    assert_eq!(
+        format!("{:?}", generic),
+        r#"Generic { name: "bytes", values: [1, 2, 3, 4] }"#,
+    );
+
+    let either: Either<u8, &str> = Either::Right("right");
+    assert_eq!(format!("{:?}", either), r#"Right("right")"#);
+}
@@ -0,0 +1,79 @@
+// The crate also provides derive(CustomDisplay), which implements Display from
+// a template given in #[display("...")] on the struct or on each enum variant.
+// Placeholders name the fields they print, `{0}` style indices refer to tuple
+// fields, and the usual format specs after a colon are kept, including width
+// arguments referring to another field.
+//
+// Bounds are inferred like for CustomDebug, except that the trait required for
+// each field follows from the placeholder that prints it: Display for `{name}`,
+// Debug for `{name:?}`, LowerHex for `{name:x}`, Pointer for `{name:p}` and so
+// on. Unit variants without a template print their name.
+
+use std::fmt::Display;
+
+use derive_debug::{CustomDebug, CustomDisplay};
+
+#[derive(CustomDebug, CustomDisplay)]
+#[display("{name} <{email}>")]
+pub struct Contact {
+    name: &'static str,
+    email: &'static str,
+}
+
+#[derive(CustomDisplay)]
+#[display("{0}:{1:>2$}")]
+pub struct Column<T>(&'static str, T, usize);
+
+#[derive(CustomDisplay)]
+pub enum Value<T> {
+    #[display("int {0:#x}")]
+    Int(u32),
+    #[display("list {items:?} ({{{count}}})")]
+    List { items: Vec<T>, count: usize },
+    Null,
+}
+
+// Raw pointers implement Pointer whatever they point to, so no bound on T is
+// needed, neither here nor for the Debug format.
+#[derive(CustomDebug, CustomDisplay)]
+#[display("at {p:p}")]
+pub struct Ptr<T> {
+    #[debug(format = "{:p}")]
+    p: *const T,
+}
+
+fn assert_display<T: Display>() {}
+
+fn main() {
+    let contact = Contact {
+        name: "Ferris",
+        email: "ferris@example.com",
+    };
+    assert_eq!(contact.to_string(), "Ferris <ferris@example.com>");
+    assert_eq!(
+        format!("{:?}", contact),
+        r#"Contact { name: "Ferris", email: "ferris@example.com" }"#,
+    );
+
+    let column = Column("id", 42, 6);
+    assert_eq!(column.to_string(), "id:    42");
+
+    // Printed with `{items:?}`, so only Debug is required.
+    #[derive(Debug)]
+    struct OnlyDebug;
+    assert_display::<Value<OnlyDebug>>();
+
+    let list: Value<u8> = Value::List {
+        items: vec![1, 2],
+        count: 2,
+    };
+    assert_eq!(list.to_string(), "list [1, 2] ({2})");
+    assert_eq!(Value::<u8>::Int(255).to_string(), "int 0xff");
+    assert_eq!(Value::<u8>::Null.to_string(), "Null");
+
+    struct NoFmt;
+    let target = NoFmt;
+    let ptr = Ptr { p: &target as *const NoFmt };
+    assert_eq!(ptr.to_string(), format!("at {:p}", ptr.p));
+    assert_eq!(format!("{:?}", ptr), format!("Ptr {{ p: {:p} }}", ptr.p));
+}
@@ -0,0 +1,14 @@
+// Placeholders in a CustomDisplay template are checked against the fields of
+// the struct or variant while the macro expands, so a typo is reported at the
+// template instead of surfacing as a confusing error in the generated code.
+
+use derive_debug::CustomDisplay;
+
+#[derive(CustomDisplay)]
+#[display("{name} <{mail}>")]
+pub struct Contact {
+    name: String,
+    email: String,
+}
+
+fn main() {}
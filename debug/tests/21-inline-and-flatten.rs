@@ -0,0 +1,106 @@
+// Alternate formatting with {:#?} puts every field of every nested value on its
+// own line, which makes deep configuration trees hard to read.
+//
+// A field marked #[debug(inline)] is always printed on a single line, even when
+// the rest of the output is in alternate mode.
+//
+// A field marked #[debug(flatten)] does not get an entry of its own. Instead
+// the fields of the nested struct are added to the parent's debug_struct, as if
+// they had been declared there. The nested struct must also derive CustomDebug
+// and opt in with #[debug(visit_fields)], whose method the parent uses to reach
+// its fields; its own field attributes still apply. Since only fields are
+// passed on, a #[debug(non_exhaustive)] on the nested struct does not add `..`
+// to the parent's output; put it on the parent instead if that is wanted.
+//
+// Under a container-level #[debug(redact_all_except("..."))], a flattened field
+// that is not listed still contributes the names of the nested fields, but each
+// value is replaced by the placeholder.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(visit_fields, non_exhaustive)]
+pub struct Retry {
+    attempts: u8,
+    #[debug(rename = "backoff_ms")]
+    backoff: u32,
+}
+
+#[derive(CustomDebug)]
+#[debug(visit_fields)]
+pub struct Server {
+    host: &'static str,
+    #[debug(inline)]
+    ports: Vec<u16>,
+    #[debug(flatten)]
+    retry: Retry,
+}
+
+#[derive(CustomDebug)]
+#[debug(redact_all_except("user"))]
+pub struct Login {
+    user: &'static str,
+    #[debug(flatten)]
+    secret: Secret,
+}
+
+#[derive(CustomDebug)]
+#[debug(visit_fields)]
+pub struct Secret {
+    password: &'static str,
+}
+
+#[derive(CustomDebug)]
+pub enum Endpoint {
+    Remote {
+        #[debug(flatten)]
+        server: Server,
+    },
+    Local(#[debug(inline)] (u8, u8)),
+}
+
+fn main() {
+    let server = Server {
+        host: "localhost",
+        ports: vec![80, 443],
+        retry: Retry {
+            attempts: 3,
+            backoff: 250,
+        },
+    };
+    assert_eq!(
+        format!("{:?}", server),
+        r#"Server { host: "localhost", ports: [80, 443], attempts: 3, backoff_ms: 250 }"#,
+    );
+
+    let expected = r#"Server {
+    host: "localhost",
+    ports: [80, 443],
+    attempts: 3,
+    backoff_ms: 250,
+}"#;
+    assert_eq!(format!("{:#?}", server), expected);
+
+    let remote = Endpoint::Remote { server };
+    let expected = r#"Remote {
+    host: "localhost",
+    ports: [80, 443],
+    attempts: 3,
+    backoff_ms: 250,
+}"#;
+    assert_eq!(format!("{:#?}", remote), expected);
+
+    let login = Login {
+        user: "admin",
+        secret: Secret {
+            password: "hunter2",
+        },
+    };
+    assert_eq!(
+        format!("{:?}", login),
+        r#"Login { user: "admin", password: <redacted> }"#,
+    );
+
+    let expected = "Local(\n    (1, 2),\n)";
+    assert_eq!(format!("{:#?}", Endpoint::Local((1, 2))), expected);
+}
@@ -0,0 +1,86 @@
+// Logging backends that record structured key-value pairs need the fields one
+// by one rather than a single Debug string. With #[debug(visit_fields)] on the
+// container, the macro also generates an inherent method
+//
+//     pub fn visit_fields(&self, visitor: &mut dyn FnMut(&'static str, &dyn Debug))
+//
+// which calls the visitor with the name and value of every field the Debug impl
+// would print, following the same skip, rename, redact, format and flatten
+// rules. Tuple fields are named by their index, and an enum visits the fields
+// of the current variant.
+
+use std::fmt::Debug;
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(visit_fields, rename_all = "camelCase")]
+pub struct Request {
+    request_id: u32,
+    #[debug(redact)]
+    auth_token: &'static str,
+    #[debug(format = "{}ms")]
+    elapsed: u64,
+    #[debug(skip)]
+    internal: u8,
+    #[debug(flatten)]
+    peer: Peer,
+}
+
+#[derive(CustomDebug)]
+#[debug(visit_fields)]
+pub struct Peer {
+    addr: &'static str,
+    #[debug(skip_if = "Option::is_none")]
+    port: Option<u16>,
+}
+
+#[derive(CustomDebug)]
+#[debug(visit_fields)]
+pub enum Outcome {
+    Success(u16),
+    Failure { reason: &'static str },
+    Pending,
+}
+
+fn collect<F>(visit: F) -> Vec<String>
+where
+    F: FnOnce(&mut dyn FnMut(&'static str, &dyn Debug)),
+{
+    let mut fields = Vec::new();
+    visit(&mut |name, value| fields.push(format!("{}={:?}", name, value)));
+    fields
+}
+
+fn main() {
+    let request = Request {
+        request_id: 7,
+        auth_token: "secret",
+        elapsed: 12,
+        internal: 0,
+        peer: Peer {
+            addr: "10.0.0.1",
+            port: None,
+        },
+    };
+    assert_eq!(
+        collect(|visitor| request.visit_fields(visitor)),
+        [
+            "requestId=7",
+            "authToken=<redacted>",
+            "elapsed=12ms",
+            r#"addr="10.0.0.1""#,
+        ],
+    );
+
+    let success = Outcome::Success(200);
+    assert_eq!(collect(|visitor| success.visit_fields(visitor)), ["0=200"]);
+
+    let failure = Outcome::Failure { reason: "timeout" };
+    assert_eq!(
+        collect(|visitor| failure.visit_fields(visitor)),
+        [r#"reason="timeout""#],
+    );
+
+    assert!(collect(|visitor| Outcome::Pending.visit_fields(visitor)).is_empty());
+}
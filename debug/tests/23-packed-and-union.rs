@@ -0,0 +1,67 @@
+// Fields of a #[repr(packed)] struct may be unaligned, and taking a reference
+// to one is an error. The macro detects repr(packed) and copies every field
+// into a local before formatting it, which, like the standard derive, requires
+// the fields to be Copy.
+//
+// Unions have no way of knowing which field is valid, so deriving on one needs
+// #[debug(unsafe_union_as = "field")] to choose the interpretation to print.
+// Only that field is read, and field attributes like format still apply to it.
+//
+// The generated Debug impl reads the field with `unsafe`, but is itself safe to
+// call, so the attribute is a promise that every value ever formatted has that
+// field initialized. Breaking it, for example by formatting a union whose
+// `&str` field was never written, is undefined behaviour. Choose a field that
+// is valid for any bits, like the integer fields below, wherever possible.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[repr(C, packed)]
+pub struct Header {
+    tag: u8,
+    #[debug(format = "{:#06x}")]
+    len: u32,
+    #[debug(skip_if = "Option::is_none")]
+    checksum: Option<u16>,
+}
+
+#[derive(CustomDebug)]
+#[repr(packed(2))]
+pub struct Pair<T: Copy>(u8, T);
+
+#[derive(CustomDebug)]
+#[debug(unsafe_union_as = "bits")]
+pub union Float {
+    value: f32,
+    #[debug(format = "{:#010x}")]
+    bits: u32,
+}
+
+#[derive(CustomDebug)]
+#[repr(packed)]
+#[debug(unsafe_union_as = "value", visit_fields)]
+pub union Packed {
+    value: u16,
+    bytes: [u8; 2],
+}
+
+fn main() {
+    let header = Header {
+        tag: 1,
+        len: 42,
+        checksum: None,
+    };
+    assert_eq!(format!("{:?}", header), "Header { tag: 1, len: 0x002a }");
+
+    let pair = Pair(1, 2u64);
+    assert_eq!(format!("{:?}", pair), "Pair(1, 2)");
+
+    let float = Float { value: 1.0 };
+    assert_eq!(format!("{:?}", float), "Float { bits: 0x3f800000 }");
+
+    let packed = Packed { value: 7 };
+    assert_eq!(format!("{:?}", packed), "Packed { value: 7 }");
+    let mut fields = Vec::new();
+    packed.visit_fields(&mut |name, value| fields.push(format!("{}={:?}", name, value)));
+    assert_eq!(fields, ["value=7"]);
+}
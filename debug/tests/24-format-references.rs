@@ -0,0 +1,69 @@
+// A format string in #[debug(format = "...")] is not limited to a single
+// placeholder for the field itself. Both `{}` and `{0}` refer to the field
+// being formatted, and any field of the same struct or variant can be referred
+// to by name, as `{unit}` or `{self.unit}`, or by index in a tuple struct, as
+// `{self.1}`. Fields can also supply the width or precision, as in `{:.prec$}`.
+//
+// Placeholders are resolved when the macro expands, so a typo is reported as
+// an error on the attribute rather than as a confusing error in generated code.
+// The trait bounds inferred for a type parameter follow the placeholders that
+// print it, so `{self.unit}` on a field of type `U` requires `U: Display`.
+
+use std::fmt::{self, Display};
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Measurement<U> {
+    #[debug(format = "{:.prec$} {self.unit}")]
+    value: f64,
+    #[debug(skip)]
+    unit: U,
+    #[debug(skip)]
+    prec: usize,
+    #[debug(format = "{0:#x} ({0})")]
+    id: u32,
+}
+
+#[derive(CustomDebug)]
+pub struct Ratio(#[debug(format = "{}/{self.1}")] u32, #[debug(skip)] u32);
+
+#[derive(CustomDebug)]
+pub enum Size {
+    Bytes {
+        #[debug(format = "{count} {unit}")]
+        count: u64,
+        #[debug(skip)]
+        unit: &'static str,
+    },
+}
+
+// Implements Display but not Debug, which is enough for `{self.unit}`.
+pub struct Meters;
+
+impl Display for Meters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("m")
+    }
+}
+
+fn main() {
+    let measurement = Measurement {
+        value: 1.2345,
+        unit: Meters,
+        prec: 2,
+        id: 255,
+    };
+    assert_eq!(
+        format!("{:?}", measurement),
+        "Measurement { value: 1.23 m, id: 0xff (255) }",
+    );
+
+    assert_eq!(format!("{:?}", Ratio(3, 4)), "Ratio(3/4)");
+
+    let size = Size::Bytes {
+        count: 512,
+        unit: "KiB",
+    };
+    assert_eq!(format!("{:?}", size), "Bytes { count: 512 KiB }");
+}
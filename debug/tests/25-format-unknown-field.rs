@@ -0,0 +1,14 @@
+// A placeholder in a field's format string that names no field of the struct is
+// reported at the attribute when the macro expands.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Measurement {
+    #[debug(format = "{} {self.units}")]
+    value: f64,
+    #[debug(skip)]
+    unit: &'static str,
+}
+
+fn main() {}
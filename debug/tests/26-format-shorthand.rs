@@ -0,0 +1,36 @@
+// The format string of a field can be given as #[debug = "..."], the syntax
+// the workshop started with, as #[debug("...")], or as the `format` key
+// alongside other keys in #[debug(...)]. All forms accept the same format
+// strings, and the shorthands can be combined with a separate #[debug(...)]
+// attribute.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Flags {
+    #[debug = "0b{:08b}"]
+    bitmask: u8,
+    #[debug(format = "0b{:08b}")]
+    keyed: u8,
+    #[debug = "{} {self.unit}"]
+    #[debug(rename = "timeout")]
+    timeout_value: u64,
+    #[debug("{}/{self.unit}")]
+    rate: u32,
+    #[debug(skip)]
+    unit: &'static str,
+}
+
+fn main() {
+    let flags = Flags {
+        bitmask: 0b00011100,
+        keyed: 0b00011100,
+        timeout_value: 30,
+        rate: 5,
+        unit: "s",
+    };
+    assert_eq!(
+        format!("{:?}", flags),
+        "Flags { bitmask: 0b00011100, keyed: 0b00011100, timeout: 30 s, rate: 5/s }",
+    );
+}
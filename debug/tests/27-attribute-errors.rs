@@ -0,0 +1,49 @@
+// Mistakes in #[debug] attributes are reported at the offending token. An
+// unknown key lists every key accepted in that position, a format string may
+// only be given once, whichever form is used, and the #[debug = "..."]
+// shorthand is only meaningful on fields. Every name in redact_all_except must
+// be a field, since a misspelled name would redact the field it meant to keep.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct UnknownKey {
+    #[debug(fmt = "{}")]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct Conflict {
+    #[debug = "{:x}"]
+    #[debug(format = "{:o}")]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct Duplicate {
+    #[debug = "{:x}"]
+    #[debug = "{:o}"]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug = "{}"]
+pub struct Container {
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug(rename_fields = "camelCase")]
+pub struct UnknownContainerKey {
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug(redact_all_except("id", "usr", "scope"))]
+pub struct Session {
+    id: u32,
+    user: &'static str,
+    scopes: Vec<&'static str>,
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+// Errors in a format string quote the placeholder at fault, and point at it
+// where the compiler supports spans inside string literals, so that a long
+// template does not have to be searched by hand.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Unterminated {
+    #[debug("{} {self.unit")]
+    value: u8,
+    unit: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct Unmatched {
+    #[debug(format = "{} unit}")]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct OtherIndex(#[debug = "{} {1}"] u8, u8);
+
+#[derive(CustomDebug)]
+pub struct UnknownWidth {
+    #[debug = "{:width$}"]
+    value: u8,
+}
+
+fn main() {}
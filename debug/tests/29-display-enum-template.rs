@@ -0,0 +1,14 @@
+// A CustomDisplay template belongs on the struct or on each enum variant. One
+// written on the enum itself would not fit variants with different fields, so
+// it is rejected rather than silently ignored.
+
+use derive_debug::CustomDisplay;
+
+#[derive(CustomDisplay)]
+#[display("{0}")]
+pub enum Value {
+    Int(u32),
+    Text(String),
+}
+
+fn main() {}
@@ -9,4 +9,25 @@ fn tests() {
     t.pass("tests/06-bound-trouble.rs");
     t.pass("tests/07-associated-type.rs");
     t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/09-enum.rs");
+    t.pass("tests/10-tuple-and-unit.rs");
+    t.pass("tests/11-skip-and-redact.rs");
+    t.pass("tests/12-with-function.rs");
+    t.pass("tests/13-rename.rs");
+    t.pass("tests/14-max-len.rs");
+    t.pass("tests/15-non-exhaustive-and-skip-if.rs");
+    t.pass("tests/16-bound-inference.rs");
+    t.compile_fail("tests/17-bound-error.rs");
+    t.pass("tests/18-generics.rs");
+    t.pass("tests/19-display.rs");
+    t.compile_fail("tests/20-display-unknown-field.rs");
+    t.pass("tests/21-inline-and-flatten.rs");
+    t.pass("tests/22-visit-fields.rs");
+    t.pass("tests/23-packed-and-union.rs");
+    t.pass("tests/24-format-references.rs");
+    t.compile_fail("tests/25-format-unknown-field.rs");
+    t.pass("tests/26-format-shorthand.rs");
+    t.compile_fail("tests/27-attribute-errors.rs");
+    t.compile_fail("tests/28-template-errors.rs");
+    t.compile_fail("tests/29-display-enum-template.rs");
 }
@@ -0,0 +1,215 @@
+use std::iter::Peekable;
+
+use proc_macro2::{Delimiter, Ident, Span, TokenStream, TokenTree};
+use syn::{Error, Lit};
+
+use crate::{lookup, range::Value};
+
+type Tokens = Peekable<std::vec::IntoIter<TokenTree>>;
+
+/// Evaluates a constant integer expression, such as `4 * 2` or `(1 << 4) - 1`,
+/// or a single character literal. Loop variables in `bindings` can be used as
+/// operands, as in `N + 1`.
+///
+/// Integers support `+ - * / % << >>`, unary minus and parentheses, with the
+/// precedence they have in Rust, and the result keeps the type suffix of its
+/// operands, as in `1u8 + 1`. Anything else, like the name of a constant,
+/// cannot be known at expansion time and is an error spanned at the whole
+/// expression.
+pub(crate) fn evaluate(tokens: Vec<TokenTree>, bindings: &[(Ident, Value)]) -> syn::Result<Value> {
+    let span = span(&tokens);
+    let mut tokens = tokens.into_iter().peekable();
+    let value = binary(&mut tokens, 0, span, bindings)?;
+    match tokens.next() {
+        Some(token) => Err(Error::new(
+            token.span(),
+            "unexpected token in constant expression",
+        )),
+        None => Ok(value),
+    }
+}
+
+/// The span of a whole expression, or of its first token where spans cannot be
+/// joined.
+pub(crate) fn span(tokens: &[TokenTree]) -> Span {
+    match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => first.span().join(last.span()).unwrap_or(first.span()),
+        _ => Span::call_site(),
+    }
+}
+
+/// The suffix of a value computed from two integers, which may leave out their
+/// suffix but not disagree on it, as with `0u8..4` or `N + 1`.
+pub(crate) fn suffix(
+    lhs: &Option<String>,
+    rhs: &Option<String>,
+    span: Span,
+) -> syn::Result<Option<String>> {
+    match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) if lhs != rhs => Err(Error::new(
+            span,
+            format!("mismatched integer suffixes `{}` and `{}`", lhs, rhs),
+        )),
+        _ => Ok(lhs.clone().or_else(|| rhs.clone())),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Shl,
+    Shr,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl Op {
+    fn precedence(self) -> u8 {
+        match self {
+            Op::Shl | Op::Shr => 1,
+            Op::Add | Op::Sub => 2,
+            Op::Mul | Op::Div | Op::Rem => 3,
+        }
+    }
+
+    fn apply(self, lhs: i128, rhs: i128) -> Option<i128> {
+        match self {
+            Op::Shl => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shl(rhs)),
+            Op::Shr => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shr(rhs)),
+            Op::Add => lhs.checked_add(rhs),
+            Op::Sub => lhs.checked_sub(rhs),
+            Op::Mul => lhs.checked_mul(rhs),
+            Op::Div => lhs.checked_div(rhs),
+            Op::Rem => lhs.checked_rem(rhs),
+        }
+    }
+}
+
+/// Parses the operator at the front of `tokens`, without consuming it.
+fn peek_op(tokens: &Tokens) -> Option<Op> {
+    let mut ahead = tokens.clone();
+    let TokenTree::Punct(punct) = ahead.next()? else {
+        return None;
+    };
+    match punct.as_char() {
+        '+' => Some(Op::Add),
+        '-' => Some(Op::Sub),
+        '*' => Some(Op::Mul),
+        '/' => Some(Op::Div),
+        '%' => Some(Op::Rem),
+        '<' | '>' => match ahead.next() {
+            Some(TokenTree::Punct(second)) if second.as_char() == punct.as_char() => {
+                Some(if punct.as_char() == '<' {
+                    Op::Shl
+                } else {
+                    Op::Shr
+                })
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn binary(
+    tokens: &mut Tokens,
+    min_precedence: u8,
+    span: Span,
+    bindings: &[(Ident, Value)],
+) -> syn::Result<Value> {
+    let mut lhs = unary(tokens, span, bindings)?;
+    while let Some(op) = peek_op(tokens) {
+        if op.precedence() <= min_precedence {
+            break;
+        }
+        let op_span = tokens.next().unwrap().span();
+        if let Op::Shl | Op::Shr = op {
+            tokens.next();
+        }
+        let rhs = binary(tokens, op.precedence(), span, bindings)?;
+        lhs = match (lhs, rhs) {
+            (Value::Int(lhs, lhs_suffix), Value::Int(rhs, rhs_suffix)) => {
+                // A shift has the type of its left operand, whatever the type
+                // of the shift amount.
+                let suffix = match op {
+                    Op::Shl | Op::Shr => lhs_suffix,
+                    _ => suffix(&lhs_suffix, &rhs_suffix, op_span)?,
+                };
+                match op.apply(lhs, rhs) {
+                    Some(value) => Value::Int(value, suffix),
+                    None => {
+                        return Err(Error::new(
+                            span,
+                            "constant expression overflows or divides by zero",
+                        ))
+                    }
+                }
+            }
+            _ => {
+                return Err(Error::new(
+                    op_span,
+                    "arithmetic is only supported on integers",
+                ))
+            }
+        };
+    }
+    Ok(lhs)
+}
+
+fn unary(tokens: &mut Tokens, span: Span, bindings: &[(Ident, Value)]) -> syn::Result<Value> {
+    let token = match tokens.next() {
+        Some(token) => token,
+        None => return Err(Error::new(span, "expected constant expression")),
+    };
+    match &token {
+        TokenTree::Punct(punct) if punct.as_char() == '-' => match unary(tokens, span, bindings)? {
+            Value::Int(value, suffix) => match value.checked_neg() {
+                Some(value) => Ok(Value::Int(value, suffix)),
+                None => Err(Error::new(
+                    span,
+                    "constant expression overflows or divides by zero",
+                )),
+            },
+            Value::Char(_) | Value::Tokens(_) => Err(Error::new(
+                punct.span(),
+                "arithmetic is only supported on integers",
+            )),
+        },
+        TokenTree::Literal(lit) => match Lit::new(lit.clone()) {
+            Lit::Int(int) => {
+                let value = int
+                    .base10_parse::<i128>()
+                    .map_err(|err| Error::new(lit.span(), err))?;
+                let suffix = Some(int.suffix()).filter(|suffix| !suffix.is_empty());
+                Ok(Value::Int(value, suffix.map(str::to_owned)))
+            }
+            Lit::Char(ch) => Ok(Value::Char(ch.value())),
+            _ => Err(Error::new(
+                lit.span(),
+                "expected integer or character literal",
+            )),
+        },
+        TokenTree::Group(group)
+            if matches!(group.delimiter(), Delimiter::Parenthesis | Delimiter::None) =>
+        {
+            evaluate(Vec::from_iter(group.stream()), bindings)
+        }
+        TokenTree::Ident(ident) => match lookup(bindings, ident) {
+            Some(Value::Tokens(_)) | None => Err(unknown(&token, span)),
+            Some(value) => Ok(value.clone()),
+        },
+        _ => Err(unknown(&token, span)),
+    }
+}
+
+fn unknown(token: &TokenTree, span: Span) -> Error {
+    Error::new(
+        span,
+        format!(
+            "cannot evaluate `{}` at expansion time, expected a constant expression",
+            TokenStream::from_iter(Some(token.clone()))
+        ),
+    )
+}
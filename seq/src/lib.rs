@@ -1,14 +1,20 @@
-use std::{iter, ops::RangeInclusive};
+use proc_macro2::{Delimiter, Group, Ident, Span, TokenStream, TokenTree};
+use syn::Error;
 
-use proc_macro2::{Delimiter, Group, Ident, Literal, TokenStream, TokenTree};
-use quote::format_ident;
-use syn::{spanned::Spanned, Error};
-
-use crate::parser::{
-    maybe_next_punct, next_braces, next_end, next_ident, next_keyword, next_punct, next_value,
+use crate::{
+    parser::{
+        maybe_next_list, maybe_next_punct, next_attributes, next_braces, next_end, next_keyword,
+        next_range, next_vars, split_commas, Input,
+    },
+    paste::{nested_vars, paste},
+    range::Value,
 };
 
+mod expr;
 mod parser;
+mod paste;
+mod range;
+mod tuples;
 
 #[proc_macro]
 pub fn seq(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -17,113 +23,376 @@ pub fn seq(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         .into()
 }
 
+#[proc_macro]
+pub fn seq_tuples(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    tuples::expand(input.into())
+        .unwrap_or_else(|error| error.into_compile_error())
+        .into()
+}
+
+/// The value of every loop variable in one iteration.
+type Bindings = Vec<(Ident, Value)>;
+
+/// The variables bound by one `vars in values` clause, and the values they
+/// take together in each iteration.
+type Clause = (Vec<Ident>, Vec<Vec<Value>>);
+
 fn internal(input: TokenStream) -> syn::Result<TokenStream> {
-    let mut input_iter = input.into_iter();
-    let var = next_ident(&mut input_iter)?;
-    next_keyword(&mut input_iter, "in")?;
-    let (start, start_span) = next_value(&mut input_iter)?;
-    next_punct(&mut input_iter, '.')?;
-    next_punct(&mut input_iter, '.')?;
-    let inclusive = maybe_next_punct(&mut input_iter, '=')?;
-    let (end, end_span) = next_value(&mut input_iter)?;
+    let mut input_iter = Input::new(input);
+    // An empty range or list expands to nothing, unless `#![deny_empty]` at the
+    // start of the input makes it an error.
+    let mut deny_empty = false;
+    for attribute in next_attributes(&mut input_iter)? {
+        if attribute == "deny_empty" {
+            deny_empty = true;
+        } else {
+            return Err(Error::new(
+                attribute.span(),
+                format!("unknown attribute `{}`, expected `deny_empty`", attribute),
+            ));
+        }
+    }
+    let mut clauses: Vec<Clause> = Vec::new();
+    loop {
+        let vars = next_vars(&mut input_iter)?;
+        for var in &vars {
+            let bound = clauses.iter().flat_map(|(vars, _)| vars).chain(&vars);
+            if bound.filter(|other| *other == var).count() > 1 {
+                return Err(Error::new(
+                    var.span(),
+                    format!("variable `{}` is bound more than once", var),
+                ));
+            }
+        }
+        next_keyword(&mut input_iter, "in")?;
+        let values = match maybe_next_list(&mut input_iter) {
+            Some((elements, span)) => {
+                if deny_empty && elements.is_empty() {
+                    return Err(Error::new(span, "Empty list"));
+                }
+                elements
+                    .into_iter()
+                    .map(|element| tuple(&vars, element, span))
+                    .collect::<syn::Result<_>>()?
+            }
+            None => {
+                let range = next_range(&mut input_iter)?;
+                if vars.len() > 1 {
+                    return Err(Error::new(
+                        range.span,
+                        "a range binds a single variable, use a list of tuples to bind several",
+                    ));
+                }
+                let values = range.values()?;
+                if deny_empty && values.is_empty() {
+                    return Err(Error::new(range.span, "Empty range"));
+                }
+                values.into_iter().map(|value| vec![value]).collect()
+            }
+        };
+        clauses.push((vars, values));
+        if !maybe_next_punct(&mut input_iter, ',')? {
+            break;
+        }
+    }
     let body = next_braces(&mut input_iter)?;
     next_end(&mut input_iter)?;
-    if (inclusive && start > end) || (!inclusive && start >= end) {
+    if has_section(body.clone()) {
+        expand_groups(&Vec::new(), &clauses, body)
+    } else {
+        Ok(TokenStream::from_iter(repeat(&product(&clauses), body)?))
+    }
+}
+
+/// The values a list element gives to each of the variables, which for several
+/// variables means splitting up a tuple. An empty element is reported at the
+/// `list_span`.
+fn tuple(vars: &[Ident], element: TokenStream, list_span: Span) -> syn::Result<Vec<Value>> {
+    if element.is_empty() {
         return Err(Error::new(
-            start_span.join(end_span).unwrap(),
-            "Empty range",
+            list_span,
+            "empty list element, remove the extra `,`",
         ));
     }
-    let range = start..=if inclusive { end } else { end - 1 };
-    let mut found_group = false;
-    let expanded = expand_groups(&var, &range, body.clone(), &mut found_group);
-    if found_group {
-        Ok(expanded)
-    } else {
-        Ok(TokenStream::from_iter(repeat(&var, range, body)))
+    if vars.len() == 1 {
+        return Ok(vec![Value::from_tokens(element)]);
+    }
+    let mut trees = element.clone().into_iter();
+    if let (Some(TokenTree::Group(group)), None) = (trees.next(), trees.next()) {
+        if group.delimiter() == Delimiter::Parenthesis {
+            let values = split_commas(group.stream());
+            if values.len() == vars.len() {
+                return Ok(values.into_iter().map(Value::from_tokens).collect());
+            }
+        }
     }
+    let span = element
+        .into_iter()
+        .next()
+        .map_or(list_span, |token| token.span());
+    Err(Error::new(
+        span,
+        format!("expected a tuple of {} values", vars.len()),
+    ))
 }
 
-fn repeat(var: &Ident, range: RangeInclusive<u64>, body: TokenStream) -> Vec<TokenTree> {
+/// Every combination of values of the clauses, varying the last clause
+/// fastest, like nested loops would.
+fn product(clauses: &[Clause]) -> Vec<Bindings> {
+    let mut iterations = vec![Vec::new()];
+    for (vars, values) in clauses {
+        let mut next = Vec::new();
+        for bindings in &iterations {
+            for tuple in values {
+                let mut bindings: Bindings = bindings.clone();
+                bindings.extend(vars.iter().cloned().zip(tuple.iter().cloned()));
+                next.push(bindings);
+            }
+        }
+        iterations = next;
+    }
+    iterations
+}
+
+fn repeat(iterations: &[Bindings], body: TokenStream) -> syn::Result<Vec<TokenTree>> {
     let mut result = Vec::new();
-    for val in range {
-        result.extend(replace_value(var, val, body.clone()));
+    for bindings in iterations {
+        result.extend(replace_value(bindings, &[], body.clone())?);
     }
-    result
+    Ok(result)
+}
+
+fn lookup<'a>(bindings: &'a [(Ident, Value)], ident: &Ident) -> Option<&'a Value> {
+    bindings
+        .iter()
+        .find(|(var, _)| var == ident)
+        .map(|(_, value)| value)
 }
 
-fn replace_value(var: &Ident, val: u64, body: TokenStream) -> TokenStream {
+/// Substitutes the variables in `body`, leaving alone the `foreign` variables
+/// of any nested `seq!` it is inside of.
+fn replace_value(
+    bindings: &[(Ident, Value)],
+    foreign: &[Ident],
+    body: TokenStream,
+) -> syn::Result<TokenStream> {
     let mut tokens = Vec::from_iter(body);
     let mut i = 0;
     while i < tokens.len() {
-        match &tokens[i] {
-            TokenTree::Ident(ident) if ident == var => {
-                let mut literal = Literal::u64_unsuffixed(val);
-                literal.set_span(ident.span());
-                tokens[i] = TokenTree::Literal(literal);
+        if paste(&mut tokens, i, bindings, foreign)? {
+            i += 1;
+            continue;
+        }
+        if let Some(value) = expression(&tokens, i, bindings, foreign)? {
+            tokens.splice(i..i + 2, Some(value));
+            i += 1;
+            continue;
+        }
+        if let TokenTree::Ident(ident) = &tokens[i] {
+            if let Some(val) = lookup(bindings, ident) {
+                tokens[i] = val.to_token(ident.span());
                 i += 1;
                 continue;
             }
-            _ => {}
-        }
-        if i + 2 < tokens.len() {
-            match &tokens[i..=i + 2] {
-                [TokenTree::Ident(prefix), TokenTree::Punct(punct), TokenTree::Ident(ident)]
-                    if ident == var && punct.as_char() == '~' =>
-                {
-                    let mut ident = format_ident!("{}{}", prefix, val);
-                    ident.set_span(prefix.span());
-                    tokens.splice(i..=i + 2, iter::once(TokenTree::Ident(ident)));
-                    i += 1;
-                    continue;
-                }
-                _ => {}
-            }
+        }
+        if let Some(name) = nested_seq(&tokens, i) {
+            let tuples = name == "seq_tuples";
+            let TokenTree::Group(group) = &mut tokens[i] else {
+                unreachable!()
+            };
+            let shadowed = nested_vars(group.stream(), tuples);
+            let bindings: Bindings = bindings
+                .iter()
+                .filter(|(var, _)| !shadowed.contains(var))
+                .cloned()
+                .collect();
+            let foreign = [foreign, &shadowed].concat();
+            let original_span = group.span();
+            let content = replace_value(&bindings, &foreign, group.stream())?;
+            *group = Group::new(group.delimiter(), content);
+            group.set_span(original_span);
+            i += 1;
+            continue;
         }
         if let TokenTree::Group(group) = &mut tokens[i] {
             let original_span = group.span();
-            let content = replace_value(var, val, group.stream());
+            let content = replace_value(bindings, foreign, group.stream())?;
             *group = Group::new(group.delimiter(), content);
             group.set_span(original_span);
         }
         i += 1;
     }
-    TokenStream::from_iter(tokens)
+    Ok(TokenStream::from_iter(tokens))
+}
+
+/// Evaluates `#(expr)` or `~(expr)` at `tokens[i]` to a literal, unless it
+/// mentions a variable of a nested `seq!`, which will evaluate it instead.
+fn expression(
+    tokens: &[TokenTree],
+    i: usize,
+    bindings: &[(Ident, Value)],
+    foreign: &[Ident],
+) -> syn::Result<Option<TokenTree>> {
+    match expression_group(tokens, i) {
+        Some(group) if mentioned(group.stream(), foreign).is_none() => {
+            let value = expr::evaluate(Vec::from_iter(group.stream()), bindings)?;
+            Ok(Some(value.to_token(group.span())))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The parentheses of an `#(expr)` or `~(expr)` at `tokens[i]`. A `~(expr)`
+/// right after an identifier is left for pasting.
+fn expression_group(tokens: &[TokenTree], i: usize) -> Option<&Group> {
+    let after_ident = i > 0 && matches!(tokens[i - 1], TokenTree::Ident(_));
+    match &tokens[i..] {
+        [TokenTree::Punct(punct), TokenTree::Group(group), ..]
+            if group.delimiter() == Delimiter::Parenthesis
+                && (punct.as_char() == '#' && section(tokens, i).is_none()
+                    || punct.as_char() == '~' && !after_ident) =>
+        {
+            Some(group)
+        }
+        _ => None,
+    }
+}
+
+/// The first of `idents` that occurs in `tokens`.
+fn mentioned(tokens: TokenStream, idents: &[Ident]) -> Option<Ident> {
+    tokens.into_iter().find_map(|token| match token {
+        TokenTree::Ident(ident) if idents.contains(&ident) => Some(ident),
+        TokenTree::Group(group) => mentioned(group.stream(), idents),
+        _ => None,
+    })
 }
 
+/// A repeated section at `tokens[i]`, either `#( ... )*` or `#( ... ),*` with
+/// a separator such as `,` or `;`: its body, its separator and the number of
+/// tokens it spans.
+fn section(tokens: &[TokenTree], i: usize) -> Option<(TokenStream, Option<TokenTree>, usize)> {
+    match &tokens[i..] {
+        [TokenTree::Punct(hash), TokenTree::Group(group), TokenTree::Punct(star), ..]
+            if hash.as_char() == '#'
+                && group.delimiter() == Delimiter::Parenthesis
+                && star.as_char() == '*' =>
+        {
+            Some((group.stream(), None, 3))
+        }
+        [TokenTree::Punct(hash), TokenTree::Group(group), separator @ TokenTree::Punct(_), TokenTree::Punct(star), ..]
+            if hash.as_char() == '#'
+                && group.delimiter() == Delimiter::Parenthesis
+                && star.as_char() == '*' =>
+        {
+            Some((group.stream(), Some(separator.clone()), 4))
+        }
+        _ => None,
+    }
+}
+
+/// Whether `tokens` contain a repeated section outside of any nested `seq!`.
+fn has_section(tokens: TokenStream) -> bool {
+    let tokens = Vec::from_iter(tokens);
+    (0..tokens.len()).any(|i| {
+        section(&tokens, i).is_some()
+            || match &tokens[i] {
+                TokenTree::Group(group) => {
+                    nested_seq(&tokens, i).is_none() && has_section(group.stream())
+                }
+                _ => false,
+            }
+    })
+}
+
+/// The name of the nested `seq!` or `seq_tuples!` invocation whose body is
+/// `tokens[i]`, if there is one.
+fn nested_seq(tokens: &[TokenTree], i: usize) -> Option<&Ident> {
+    match (i.checked_sub(2).map(|start| &tokens[start..i]), &tokens[i]) {
+        (Some([TokenTree::Ident(name), TokenTree::Punct(bang)]), TokenTree::Group(_))
+            if (name == "seq" || name == "seq_tuples") && bang.as_char() == '!' =>
+        {
+            Some(name)
+        }
+        _ => None,
+    }
+}
+
+/// Expands the repeated sections in `body`.
+///
+/// A section repeats over every combination of the `clauses`, unless it
+/// contains sections of its own. Then it repeats over the first clause only,
+/// and the sections inside repeat over the clauses after it, so nesting
+/// follows the order in which the variables are bound. The values of the
+/// enclosing sections are in `outer`.
+///
+/// Expressions outside of any section are evaluated here, since the sections
+/// are all that `replace_value` is run on. They cannot use the variables of
+/// the `clauses`, which are only bound inside the sections.
 fn expand_groups(
-    var: &Ident,
-    range: &RangeInclusive<u64>,
+    outer: &Bindings,
+    clauses: &[Clause],
     body: TokenStream,
-    found: &mut bool,
-) -> TokenStream {
+) -> syn::Result<TokenStream> {
     let mut tokens = Vec::from_iter(body);
     let mut i = 0;
     while i < tokens.len() {
-        if i + 2 < tokens.len() {
-            match &tokens[i..=i + 2] {
-                [TokenTree::Punct(left), TokenTree::Group(group), TokenTree::Punct(right)]
-                    if left.as_char() == '#'
-                        && group.delimiter() == Delimiter::Parenthesis
-                        && right.as_char() == '*' =>
-                {
-                    *found = true;
-                    let repeated = repeat(var, range.clone(), group.stream());
-                    let len = repeated.len();
-                    tokens.splice(i..=i + 2, repeated);
-                    i += len;
-                    continue;
+        if let Some((inner, separator, len)) = section(&tokens, i) {
+            if clauses.is_empty() {
+                return Err(Error::new(
+                    tokens[i + 1].span(),
+                    "nested repetition has no variable left to repeat over, \
+                     bind one more variable for each level of nesting",
+                ));
+            }
+            let (level, rest) = if has_section(inner.clone()) {
+                clauses.split_at(1)
+            } else {
+                (clauses, &[][..])
+            };
+            let mut repeated = Vec::new();
+            for (n, bindings) in product(level).into_iter().enumerate() {
+                let bindings = [outer.as_slice(), &bindings].concat();
+                if let (Some(separator), 1..) = (&separator, n) {
+                    repeated.push(separator.clone());
                 }
-                _ => {}
+                let element = expand_groups(&bindings, rest, inner.clone())?;
+                repeated.extend(replace_value(&bindings, &[], element)?);
+            }
+            let repeated_len = repeated.len();
+            tokens.splice(i..i + len, repeated);
+            i += repeated_len;
+            continue;
+        }
+        if let Some(group) = expression_group(&tokens, i) {
+            let unbound: Vec<Ident> = clauses.iter().flat_map(|(vars, _)| vars.clone()).collect();
+            if let Some(var) = mentioned(group.stream(), &unbound) {
+                return Err(Error::new(
+                    var.span(),
+                    format!(
+                        "`{}` is only bound inside a repeated section, \
+                         move the expression into one",
+                        var
+                    ),
+                ));
             }
+            let value = expr::evaluate(Vec::from_iter(group.stream()), outer)?;
+            tokens.splice(i..i + 2, Some(value.to_token(group.span())));
+            i += 1;
+            continue;
+        }
+        // The repeated sections of a nested `seq!` belong to it, so only the
+        // variables are substituted in its body.
+        if nested_seq(&tokens, i).is_some() {
+            i += 1;
+            continue;
         }
         if let TokenTree::Group(group) = &mut tokens[i] {
             let original_span = group.span();
-            let content = expand_groups(var, range, group.stream(), found);
+            let content = expand_groups(outer, clauses, group.stream())?;
             *group = Group::new(group.delimiter(), content);
             group.set_span(original_span);
         }
         i += 1;
     }
-    TokenStream::from_iter(tokens)
+    Ok(TokenStream::from_iter(tokens))
 }
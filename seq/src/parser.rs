@@ -1,26 +1,90 @@
 use std::fmt::Display;
 
-use proc_macro2::{token_stream::IntoIter, Delimiter, Ident, Span, TokenStream, TokenTree};
+use proc_macro2::{
+    token_stream::IntoIter, Delimiter, Group, Ident, Spacing, Span, TokenStream, TokenTree,
+};
 use syn::Error;
 
-fn next(iter: &mut IntoIter) -> syn::Result<TokenTree> {
-    iter.next()
-        .ok_or_else(|| Error::new(Span::call_site(), "unexpected end of input".to_owned()))
+use crate::{
+    expr,
+    range::{Adapter, Range, Value},
+};
+
+/// The tokens of the macro input or of a group in it, which remembers where
+/// they end so that running out of tokens is reported next to the nearest one
+/// rather than at the whole invocation.
+#[derive(Clone)]
+pub(crate) struct Input {
+    iter: IntoIter,
+    /// The span of the last token consumed.
+    last: Span,
+    /// The closing delimiter, for the tokens inside a group.
+    close: Option<Span>,
+}
+
+impl Input {
+    pub(crate) fn new(tokens: TokenStream) -> Input {
+        Input {
+            iter: tokens.into_iter(),
+            last: Span::call_site(),
+            close: None,
+        }
+    }
+
+    fn inside(group: &Group) -> Input {
+        Input {
+            iter: group.stream().into_iter(),
+            last: group.span_open(),
+            close: Some(group.span_close()),
+        }
+    }
+
+    fn peek(&self) -> Option<TokenTree> {
+        self.iter.clone().next()
+    }
+
+    /// The span of the next token, or of where the tokens end.
+    fn peek_span(&self) -> Span {
+        match self.peek() {
+            Some(token) => token.span(),
+            None => self.close.unwrap_or(self.last),
+        }
+    }
+}
+
+impl Iterator for Input {
+    type Item = TokenTree;
+
+    fn next(&mut self) -> Option<TokenTree> {
+        let token = self.iter.next()?;
+        self.last = token.span();
+        Some(token)
+    }
+}
+
+fn next(iter: &mut Input, expected: impl Display) -> syn::Result<TokenTree> {
+    let span = iter.peek_span();
+    iter.next().ok_or_else(|| {
+        Error::new(
+            span,
+            format!("unexpected end of input, expected {}", expected),
+        )
+    })
 }
 
 fn error(span: TokenTree, message: impl Display) -> Error {
     Error::new(span.span(), message)
 }
 
-pub(crate) fn next_ident(iter: &mut IntoIter) -> syn::Result<Ident> {
-    match next(iter)? {
+pub(crate) fn next_ident(iter: &mut Input) -> syn::Result<Ident> {
+    match next(iter, "identifier")? {
         TokenTree::Ident(ident) => Ok(ident),
-        other => Err(error(other, "expected ident")),
+        other => Err(error(other, "expected identifier")),
     }
 }
 
-pub(crate) fn next_keyword(iter: &mut IntoIter, keyword: &str) -> syn::Result<()> {
-    let token = next(iter)?;
+pub(crate) fn next_keyword(iter: &mut Input, keyword: &str) -> syn::Result<()> {
+    let token = next(iter, format_args!("`{}`", keyword))?;
     if let TokenTree::Ident(ident) = &token {
         if *ident == keyword {
             return Ok(());
@@ -29,29 +93,229 @@ pub(crate) fn next_keyword(iter: &mut IntoIter, keyword: &str) -> syn::Result<()
     Err(error(token, format!("expected `{}`", keyword)))
 }
 
-pub(crate) fn next_value(iter: &mut IntoIter) -> syn::Result<(u64, Span)> {
-    let token = next(iter)?;
-    match &token {
-        TokenTree::Literal(lit) => {
-            if let Ok(value) = lit.to_string().parse::<u64>() {
-                return Ok((value, lit.span()));
+/// Parses the inner attributes at the start of the input, as in
+/// `#![deny_empty]`, returning their names.
+pub(crate) fn next_attributes(iter: &mut Input) -> syn::Result<Vec<Ident>> {
+    let mut names = Vec::new();
+    while maybe_next_punct(iter, '#')? {
+        next_punct(iter, '!')?;
+        let mut inner = Input::inside(&next_group(iter, Delimiter::Bracket, "square brackets")?);
+        let name = next_ident(&mut inner)?;
+        if let Some(token) = inner.next() {
+            return Err(error(token, format!("`{}` takes no arguments", name)));
+        }
+        names.push(name);
+    }
+    Ok(names)
+}
+
+/// Parses the variables of a loop, either a single identifier or several in
+/// parentheses, as in `(T, N)`.
+pub(crate) fn next_vars(iter: &mut Input) -> syn::Result<Vec<Ident>> {
+    if let Some(TokenTree::Group(group)) = iter.peek() {
+        if group.delimiter() == Delimiter::Parenthesis {
+            iter.next();
+            let mut inner = Input::inside(&group);
+            let mut vars = vec![next_ident(&mut inner)?];
+            while maybe_next_punct(&mut inner, ',')? {
+                if inner.peek().is_none() {
+                    break;
+                }
+                vars.push(next_ident(&mut inner)?);
             }
+            if let Some(token) = inner.next() {
+                return Err(error(token, "expected `,` between variables"));
+            }
+            return Ok(vars);
         }
-        TokenTree::Group(group) if group.delimiter() == Delimiter::None => {
-            if let Some(TokenTree::Literal(lit)) = group.stream().into_iter().next() {
-                if let Ok(value) = lit.to_string().parse::<u64>() {
-                    return Ok((value, lit.span()));
+    }
+    Ok(vec![next_ident(iter)?])
+}
+
+/// Parses a list of values in square brackets, if there is one, returning its
+/// elements and its span.
+pub(crate) fn maybe_next_list(iter: &mut Input) -> Option<(Vec<TokenStream>, Span)> {
+    match iter.peek() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
+            iter.next();
+            Some((split_commas(group.stream()), group.span()))
+        }
+        _ => None,
+    }
+}
+
+/// Splits `tokens` at every comma that is not nested in a group or between the
+/// angle brackets of generic arguments, so that list elements like
+/// `HashMap<K, V>` stay whole. A `<` only opens generic arguments at the start
+/// of an element or after an identifier, `::` or `>`, so `1 < 2, true` is two
+/// elements. A `<<` is a shift rather than two brackets, and so is a `>>`
+/// outside of any brackets, so `1 << 2, 3` is two elements. A trailing comma is
+/// allowed.
+pub(crate) fn split_commas(tokens: TokenStream) -> Vec<TokenStream> {
+    let mut elements = Vec::new();
+    let mut current = Vec::new();
+    let mut depth = 0usize;
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        if let TokenTree::Punct(punct) = &token {
+            let shift = punct.spacing() == Spacing::Joint
+                && matches!(
+                    tokens.peek(),
+                    Some(TokenTree::Punct(next)) if next.as_char() == punct.as_char()
+                );
+            let previous = match current.last() {
+                Some(TokenTree::Punct(previous)) => Some(previous.as_char()),
+                _ => None,
+            };
+            match punct.as_char() {
+                ',' if depth == 0 => {
+                    elements.push(TokenStream::from_iter(current.drain(..)));
+                    continue;
+                }
+                '<' | '>' if shift && (punct.as_char() == '<' || depth == 0) => {
+                    current.push(token);
+                    current.extend(tokens.next());
+                    continue;
+                }
+                // Not the less-than of `1 < 2` or `(a + b) < c`.
+                '<' if matches!(current.last(), None | Some(TokenTree::Ident(_)))
+                    || matches!(previous, Some(':' | '>')) =>
+                {
+                    depth += 1
+                }
+                // Not the closing bracket of `->` or `=>`.
+                '>' if previous != Some('-') && previous != Some('=') => {
+                    depth = depth.saturating_sub(1)
                 }
+                _ => {}
             }
         }
-        _ => {}
-    };
-    Err(Error::new(token.span(), "expected numeric literal"))
+        current.push(token);
+    }
+    if !current.is_empty() {
+        elements.push(TokenStream::from_iter(current));
+    }
+    elements
+}
+
+/// Parses a range, either as `start..end` and `start..=end`, or wrapped in
+/// parentheses and followed by any of `.step_by(n)` and `.rev()`.
+pub(crate) fn next_range(iter: &mut Input) -> syn::Result<Range> {
+    if let Some(TokenTree::Group(group)) = iter.peek() {
+        if group.delimiter() == Delimiter::Parenthesis && contains_range(group.stream()) {
+            iter.next();
+            let mut inner = Input::inside(&group);
+            let mut range = next_range(&mut inner)?;
+            next_end(&mut inner)?;
+            while maybe_next_punct(iter, '.')? {
+                let method = next_ident(iter)?;
+                let arguments = next_group(iter, Delimiter::Parenthesis, "parentheses")?;
+                let mut argument = Input::inside(&arguments);
+                let adapter = if method == "step_by" {
+                    if argument.peek().is_none() {
+                        return Err(Error::new(arguments.span(), "expected a positive step"));
+                    }
+                    let argument = Vec::from_iter(argument);
+                    let span = expr::span(&argument);
+                    match expr::evaluate(argument, &[])? {
+                        Value::Int(step, _) if step > 0 => match usize::try_from(step) {
+                            Ok(step) => Adapter::StepBy(step),
+                            Err(_) => return Err(Error::new(span, "step does not fit in `usize`")),
+                        },
+                        _ => return Err(Error::new(span, "expected a positive step")),
+                    }
+                } else if method == "rev" {
+                    if let Some(token) = argument.next() {
+                        return Err(error(token, "`rev` takes no arguments"));
+                    }
+                    Adapter::Rev
+                } else {
+                    return Err(Error::new(method.span(), "expected `step_by` or `rev`"));
+                };
+                range.adapters.push(adapter);
+            }
+            return Ok(range);
+        }
+    }
+    let (start, start_span) = next_bound(iter, true)?;
+    let inclusive = next_dots(iter)?;
+    let (end, end_span) = next_bound(iter, false)?;
+    Ok(Range {
+        start,
+        end,
+        inclusive,
+        adapters: Vec::new(),
+        span: start_span.join(end_span).unwrap_or(start_span),
+    })
+}
+
+/// Parses the `..` or `..=` of a range, returning whether it is inclusive.
+fn next_dots(iter: &mut Input) -> syn::Result<bool> {
+    let token = next(iter, "`..` or `..=`")?;
+    match (&token, iter.peek()) {
+        (TokenTree::Punct(first), Some(TokenTree::Punct(second)))
+            if first.as_char() == '.' && second.as_char() == '.' =>
+        {
+            iter.next();
+        }
+        _ => {
+            return Err(error(
+                token,
+                "expected `..` or `..=` after the start of the range",
+            ))
+        }
+    }
+    match iter.peek() {
+        Some(TokenTree::Punct(third)) if third.as_char() == '.' => Err(Error::new(
+            third.span(),
+            "unexpected `...`, did you mean `..=`?",
+        )),
+        _ => maybe_next_punct(iter, '='),
+    }
+}
+
+/// Whether `tokens` contain a `..`, which tells a parenthesized range apart from
+/// a parenthesized bound like `(1 << 2) - 1..8`.
+fn contains_range(tokens: TokenStream) -> bool {
+    let tokens = Vec::from_iter(tokens);
+    tokens.windows(2).any(|pair| match pair {
+        [TokenTree::Punct(first), TokenTree::Punct(second)] => {
+            first.as_char() == '.' && second.as_char() == '.'
+        }
+        _ => false,
+    })
 }
 
-pub(crate) fn maybe_next_punct(iter: &mut IntoIter, ch: char) -> syn::Result<bool> {
-    let present = match iter.clone().next() {
-        Some(TokenTree::Punct(_)) => {
+/// Parses a range bound, which is a constant expression running up to the `..`
+/// of the range for the start, and up to the next variable or the body for
+/// either bound.
+fn next_bound(iter: &mut Input, start: bool) -> syn::Result<(Value, Span)> {
+    let mut tokens = Vec::new();
+    loop {
+        let mut ahead = iter.iter.clone();
+        match (ahead.next(), ahead.next()) {
+            (None, _) => break,
+            (Some(TokenTree::Punct(first)), Some(TokenTree::Punct(second)))
+                if start && first.as_char() == '.' && second.as_char() == '.' =>
+            {
+                break
+            }
+            (Some(TokenTree::Group(group)), _) if group.delimiter() == Delimiter::Brace => break,
+            (Some(TokenTree::Punct(punct)), _) if punct.as_char() == ',' => break,
+            _ => tokens.push(iter.next().unwrap()),
+        }
+    }
+    if tokens.is_empty() {
+        return Err(Error::new(iter.peek_span(), "expected range bound"));
+    }
+    let span = expr::span(&tokens);
+    Ok((expr::evaluate(tokens, &[])?, span))
+}
+
+/// Consumes the next token if it is the punctuation `ch`.
+pub(crate) fn maybe_next_punct(iter: &mut Input, ch: char) -> syn::Result<bool> {
+    let present = match iter.peek() {
+        Some(TokenTree::Punct(punct)) if punct.as_char() == ch => {
             next_punct(iter, ch)?;
             true
         }
@@ -60,8 +324,8 @@ pub(crate) fn maybe_next_punct(iter: &mut IntoIter, ch: char) -> syn::Result<boo
     Ok(present)
 }
 
-pub(crate) fn next_punct(iter: &mut IntoIter, char: char) -> syn::Result<()> {
-    let token = next(iter)?;
+pub(crate) fn next_punct(iter: &mut Input, char: char) -> syn::Result<()> {
+    let token = next(iter, format_args!("`{}`", char))?;
     if let TokenTree::Punct(punct) = &token {
         if punct.as_char() == char {
             return Ok(());
@@ -70,17 +334,21 @@ pub(crate) fn next_punct(iter: &mut IntoIter, char: char) -> syn::Result<()> {
     Err(error(token, format!("expected `{}`", char)))
 }
 
-pub(crate) fn next_braces(iter: &mut IntoIter) -> syn::Result<TokenStream> {
-    let token = next(iter)?;
+pub(crate) fn next_braces(iter: &mut Input) -> syn::Result<TokenStream> {
+    Ok(next_group(iter, Delimiter::Brace, "curly braces")?.stream())
+}
+
+fn next_group(iter: &mut Input, delimiter: Delimiter, name: &str) -> syn::Result<Group> {
+    let token = next(iter, name)?;
     if let TokenTree::Group(group) = &token {
-        if group.delimiter() == Delimiter::Brace {
-            return Ok(group.stream());
+        if group.delimiter() == delimiter {
+            return Ok(group.clone());
         }
     }
-    Err(error(token, "expected curly braces"))
+    Err(error(token, format!("expected {}", name)))
 }
 
-pub(crate) fn next_end(iter: &mut IntoIter) -> syn::Result<()> {
+pub(crate) fn next_end(iter: &mut Input) -> syn::Result<()> {
     match iter.next() {
         Some(token) => Err(error(token, "unexpected token")),
         None => Ok(()),
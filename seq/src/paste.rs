@@ -0,0 +1,203 @@
+use proc_macro2::{Delimiter, Group, Ident, Span, TokenStream, TokenTree};
+use syn::{ext::IdentExt, Error};
+
+use crate::{expr, lookup, mentioned, range::Value};
+
+/// How an integer is written when pasted, given in brackets after the
+/// variable as in `Reg~N~[02]` or `Reg~N~[04X]`: an optional zero-padded width
+/// followed by an optional radix, `x`, `X`, `o` or `b`. The brackets keep it
+/// apart from a type or field ascription like `arg~N: X`.
+#[derive(Clone, Copy)]
+struct Spec {
+    width: usize,
+    radix: Option<char>,
+}
+
+impl Spec {
+    fn parse(group: &Group) -> syn::Result<Spec> {
+        let mut tokens = group.stream().into_iter();
+        let text = match (tokens.next(), tokens.next()) {
+            (Some(TokenTree::Ident(ident)), None) => ident.to_string(),
+            (Some(TokenTree::Literal(literal)), None) => literal.to_string(),
+            _ => String::new(),
+        };
+        Spec::from_text(&text).ok_or_else(|| {
+            Error::new(
+                group.span(),
+                "expected a format like `[02]`, `[x]` or `[04X]`",
+            )
+        })
+    }
+
+    fn from_text(text: &str) -> Option<Spec> {
+        let (width, radix) = match text.char_indices().last()? {
+            (i, ch @ ('x' | 'X' | 'o' | 'b')) => (&text[..i], Some(ch)),
+            _ => (text, None),
+        };
+        let width = match width {
+            "" => 0,
+            // Only zero padding makes sense in an identifier.
+            width if width.starts_with('0') && width.len() > 1 => width.parse().ok()?,
+            _ => return None,
+        };
+        Some(Spec { width, radix })
+    }
+
+    fn format(self, value: u128) -> String {
+        let width = self.width;
+        match self.radix {
+            Some('x') => format!("{:0width$x}", value),
+            Some('X') => format!("{:0width$X}", value),
+            Some('o') => format!("{:0width$o}", value),
+            Some('b') => format!("{:0width$b}", value),
+            _ => format!("{:0width$}", value),
+        }
+    }
+}
+
+/// Pastes the pieces joined by `~` starting at `tokens[i]` into a single
+/// identifier, as in `f~N`, `T~_MAX`, `pre~N~post` or `field~(N + 1)`, and
+/// returns whether anything was pasted.
+///
+/// Only chains containing one of the `bindings` are pasted, and identifiers in
+/// `foreign` are never pasted, since they are the variables of a nested `seq!`
+/// that will paste them itself later. So the outer of two nested loops turns
+/// `f~I~J` into `f0~J`.
+pub(crate) fn paste(
+    tokens: &mut Vec<TokenTree>,
+    i: usize,
+    bindings: &[(Ident, Value)],
+    foreign: &[Ident],
+) -> syn::Result<bool> {
+    let mut pieces = Vec::new();
+    let mut end = i;
+    while let Some(piece) = piece(tokens, end, !pieces.is_empty(), bindings, foreign)? {
+        end = piece.end;
+        pieces.push(piece);
+        match tokens.get(end) {
+            Some(TokenTree::Punct(tilde)) if tilde.as_char() == '~' => end += 1,
+            _ => break,
+        }
+    }
+    if pieces.len() < 2 || pieces.iter().all(|piece| piece.value.is_none()) {
+        return Ok(false);
+    }
+    let end = pieces.last().unwrap().end;
+    let mut text = String::new();
+    for piece in &pieces {
+        text.push_str(&piece.text(&tokens[piece.start..piece.end])?);
+    }
+    let span = tokens[i].span();
+    let mut ident: Ident = syn::parse_str(&text)
+        .map_err(|_| Error::new(span, format!("`{}` is not a valid identifier", text)))?;
+    ident.set_span(span);
+    tokens.splice(i..end, Some(TokenTree::Ident(ident)));
+    Ok(true)
+}
+
+/// One identifier, variable or `(expr)`, with its format, in a chain joined by
+/// `~`.
+struct Piece {
+    /// The value of a variable or expression, or `None` for an identifier
+    /// that is pasted as written.
+    value: Option<Value>,
+    name: String,
+    span: Span,
+    spec: Option<Spec>,
+    start: usize,
+    end: usize,
+}
+
+impl Piece {
+    fn text(&self, tokens: &[TokenTree]) -> syn::Result<String> {
+        match (&self.value, self.spec) {
+            (None, _) => Ok(self.name.clone()),
+            (Some(value), None) => value.to_paste(self.span),
+            (Some(Value::Int(int, _)), Some(spec)) if *int >= 0 => Ok(spec.format(*int as u128)),
+            (Some(value @ Value::Int(..)), Some(_)) => value.to_paste(self.span),
+            (Some(_), Some(_)) => Err(Error::new(
+                expr::span(tokens),
+                "a format like `[02]` or `[x]` only applies to integers",
+            )),
+        }
+    }
+}
+
+/// The piece at `tokens[i]`. An `(expr)` is only a piece after a `~`, where it
+/// cannot be ordinary parentheses.
+fn piece(
+    tokens: &[TokenTree],
+    i: usize,
+    after_tilde: bool,
+    bindings: &[(Ident, Value)],
+    foreign: &[Ident],
+) -> syn::Result<Option<Piece>> {
+    let (value, name, span) = match tokens.get(i) {
+        Some(TokenTree::Ident(ident)) if !foreign.contains(ident) => (
+            lookup(bindings, ident).cloned(),
+            ident.unraw().to_string(),
+            ident.span(),
+        ),
+        Some(TokenTree::Group(group))
+            if after_tilde
+                && group.delimiter() == Delimiter::Parenthesis
+                && mentioned(group.stream(), foreign).is_none() =>
+        {
+            let value = expr::evaluate(Vec::from_iter(group.stream()), bindings)?;
+            (Some(value), String::new(), group.span())
+        }
+        _ => return Ok(None),
+    };
+    let spec = match (&value, tokens.get(i + 1), tokens.get(i + 2)) {
+        (Some(_), Some(TokenTree::Punct(tilde)), Some(TokenTree::Group(group)))
+            if tilde.as_char() == '~' && group.delimiter() == Delimiter::Bracket =>
+        {
+            Some(Spec::parse(group)?)
+        }
+        _ => None,
+    };
+    Ok(Some(Piece {
+        value,
+        name,
+        span,
+        spec,
+        start: i,
+        end: if spec.is_some() { i + 3 } else { i + 1 },
+    }))
+}
+
+/// The variables declared in the header of a nested `seq!`, which shadow the
+/// outer variables of the same name within it. A nested `seq_tuples!` also
+/// declares its index, `I` unless named after the range.
+pub(crate) fn nested_vars(input: TokenStream, tuples: bool) -> Vec<Ident> {
+    let tokens = Vec::from_iter(input);
+    let mut vars = Vec::new();
+    for (i, pair) in tokens.windows(2).enumerate() {
+        match pair {
+            [TokenTree::Group(group), TokenTree::Ident(keyword)] if keyword == "in" => {
+                vars.extend(group.stream().into_iter().filter_map(|token| match token {
+                    TokenTree::Ident(ident) => Some(ident),
+                    _ => None,
+                }));
+            }
+            [TokenTree::Ident(var), TokenTree::Ident(keyword)] if keyword == "in" => {
+                vars.push(var.clone());
+            }
+            [last, TokenTree::Group(group)] if group.delimiter() == Delimiter::Brace => {
+                if tuples {
+                    let after_comma = matches!(
+                        i.checked_sub(1).map(|i| &tokens[i]),
+                        Some(TokenTree::Punct(comma)) if comma.as_char() == ','
+                    );
+                    vars.push(match last {
+                        TokenTree::Ident(index) if after_comma => index.clone(),
+                        _ => Ident::new("I", Span::call_site()),
+                    });
+                }
+                break;
+            }
+            _ => {}
+        }
+    }
+    vars
+}
@@ -0,0 +1,173 @@
+use proc_macro2::{Delimiter, Group, Literal, Span, TokenStream, TokenTree};
+use syn::{ext::IdentExt, Error};
+
+use crate::expr;
+
+/// A value the loop variable takes.
+#[derive(Clone, Debug)]
+pub(crate) enum Value {
+    /// An integer and its type suffix, as in `0u8`, which substituted
+    /// literals keep.
+    Int(i128, Option<String>),
+    Char(char),
+    /// An element of a list that is not a constant, such as a type.
+    Tokens(TokenStream),
+}
+
+impl Value {
+    /// An element of a list, which is an integer or a character if it is a
+    /// constant expression and substituted as written otherwise.
+    pub(crate) fn from_tokens(tokens: TokenStream) -> Value {
+        expr::evaluate(Vec::from_iter(tokens.clone()), &[]).unwrap_or(Value::Tokens(tokens))
+    }
+
+    /// The tokens substituted for the loop variable. Several tokens are kept
+    /// together in an invisible group, the way `macro_rules!` passes on an
+    /// `expr` or `ty` fragment. A negative number is parenthesized, so that
+    /// `N.pow(2)` squares `-3` instead of negating `3.pow(2)`; rustc does not
+    /// treat an invisible group as an operand of a method call.
+    pub(crate) fn to_token(&self, span: Span) -> TokenTree {
+        let mut literal = match self {
+            Value::Int(value, None) => Literal::i128_unsuffixed(*value),
+            Value::Int(value, Some(suffix)) => format!("{}{}", value, suffix).parse().unwrap(),
+            Value::Char(value) => Literal::character(*value),
+            Value::Tokens(tokens) => {
+                let mut trees = tokens.clone().into_iter();
+                return match (trees.next(), trees.next()) {
+                    (Some(tree), None) => tree,
+                    _ => {
+                        let mut group = Group::new(Delimiter::None, tokens.clone());
+                        group.set_span(span);
+                        TokenTree::Group(group)
+                    }
+                };
+            }
+        };
+        literal.set_span(span);
+        match self {
+            Value::Int(value, _) if *value < 0 => {
+                let literal = TokenTree::Literal(literal);
+                let mut group = Group::new(Delimiter::Parenthesis, TokenStream::from(literal));
+                group.set_span(span);
+                TokenTree::Group(group)
+            }
+            _ => TokenTree::Literal(literal),
+        }
+    }
+
+    /// The text pasted into an identifier by `prefix~N`.
+    pub(crate) fn to_paste(&self, span: Span) -> syn::Result<String> {
+        match self {
+            Value::Int(value, _) if *value < 0 => Err(Error::new(
+                span,
+                format!("cannot paste negative value {} into an identifier", value),
+            )),
+            Value::Int(value, _) => Ok(value.to_string()),
+            Value::Char(value) => Ok(value.to_string()),
+            Value::Tokens(tokens) => {
+                let mut trees = tokens.clone().into_iter();
+                match (trees.next(), trees.next()) {
+                    (Some(TokenTree::Ident(ident)), None) => Ok(ident.unraw().to_string()),
+                    (Some(TokenTree::Literal(literal)), None) => Ok(literal.to_string()),
+                    _ => Err(Error::new(
+                        span,
+                        format!("cannot paste `{}` into an identifier", tokens),
+                    )),
+                }
+            }
+        }
+    }
+}
+
+/// The range a loop variable iterates over, as in `0..8`, `'a'..='z'`,
+/// `(0..64).step_by(8)` or `(0..8).rev()`.
+pub(crate) struct Range {
+    pub(crate) start: Value,
+    pub(crate) end: Value,
+    pub(crate) inclusive: bool,
+    /// The iterator methods called on the range, in order.
+    pub(crate) adapters: Vec<Adapter>,
+    pub(crate) span: Span,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Adapter {
+    StepBy(usize),
+    Rev,
+}
+
+impl Range {
+    /// The values of the range, computed from its bounds and adapters without
+    /// going through the values that `step_by` skips.
+    pub(crate) fn values(&self) -> syn::Result<Vec<Value>> {
+        let (start, end, suffix) = match (&self.start, &self.end) {
+            (Value::Int(start, start_suffix), Value::Int(end, end_suffix)) => {
+                let suffix = expr::suffix(start_suffix, end_suffix, self.span)?;
+                (*start, *end, Some(suffix))
+            }
+            (Value::Char(start), Value::Char(end)) => (char_index(*start), char_index(*end), None),
+            _ => {
+                return Err(Error::new(
+                    self.span,
+                    "range bounds must both be integers or both be characters",
+                ))
+            }
+        };
+        let last = if self.inclusive {
+            Some(end)
+        } else {
+            end.checked_sub(1)
+        };
+        // The values are `first + k * step` for `k` in `0..count`. They all fit
+        // in an `i128`, so wrapping arithmetic gives them exactly even where
+        // `step` itself or an intermediate product does not fit.
+        let mut first = start;
+        let mut step = 1i128;
+        let mut count = match last {
+            Some(last) if last >= start => last
+                .abs_diff(start)
+                .checked_add(1)
+                .ok_or_else(|| Error::new(self.span, "range is too long"))?,
+            _ => 0,
+        };
+        for adapter in &self.adapters {
+            match *adapter {
+                Adapter::StepBy(by) => {
+                    count = count.div_ceil(by as u128);
+                    step = step.wrapping_mul(by as i128);
+                }
+                Adapter::Rev => {
+                    if count > 0 {
+                        first = first.wrapping_add(step.wrapping_mul((count - 1) as i128));
+                    }
+                    step = step.wrapping_neg();
+                }
+            }
+        }
+        let values = (0..count).map(|k| first.wrapping_add((k as i128).wrapping_mul(step)));
+        Ok(match suffix {
+            Some(suffix) => values
+                .map(|value| Value::Int(value, suffix.clone()))
+                .collect(),
+            None => values.map(|index| Value::Char(index_char(index))).collect(),
+        })
+    }
+}
+
+/// The position of `ch` among all characters, which skips the surrogate code
+/// points the way ranges of `char` do.
+fn char_index(ch: char) -> i128 {
+    match ch as i128 {
+        code if code > 0xDFFF => code - 0x800,
+        code => code,
+    }
+}
+
+fn index_char(index: i128) -> char {
+    let code = if index >= 0xD800 {
+        index + 0x800
+    } else {
+        index
+    };
+    char::from_u32(code as u32).unwrap()
+}
@@ -0,0 +1,56 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use syn::Error;
+
+use crate::{
+    expand_groups,
+    parser::{
+        maybe_next_punct, next_braces, next_end, next_ident, next_keyword, next_range, Input,
+    },
+    range::Value,
+    replace_value,
+};
+
+/// Expands `seq_tuples!(N in 1..=16 { ... })`, which is like `seq!` except that
+/// the repeated sections of the body repeat over a variable `I` running through
+/// `0..N`. So every value of `N` gets a copy of the body with a growing prefix
+/// of `I`, which is what a trait implementation for each tuple arity needs, as
+/// in `impl<#(T~I,)*> Trait for (#(T~I,)*) {}`. The index can be given another
+/// name after the range, as in `seq_tuples!(N in 1..=16, J { ... })`.
+pub(crate) fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let mut input_iter = Input::new(input);
+    let var = next_ident(&mut input_iter)?;
+    next_keyword(&mut input_iter, "in")?;
+    let range = next_range(&mut input_iter)?;
+    let index = if maybe_next_punct(&mut input_iter, ',')? {
+        next_ident(&mut input_iter)?
+    } else {
+        Ident::new("I", Span::call_site())
+    };
+    if var == index {
+        return Err(Error::new(
+            var.span(),
+            format!(
+                "`{}` is also the name of the index, name the index differently after the \
+                 range, as in `{} in ..., J`",
+                var, var
+            ),
+        ));
+    }
+    let body = next_braces(&mut input_iter)?;
+    next_end(&mut input_iter)?;
+    let mut expanded = TokenStream::new();
+    for value in range.values()? {
+        let arity = match value {
+            Value::Int(arity, _) if arity >= 0 => arity,
+            _ => return Err(Error::new(range.span, "expected a range of tuple arities")),
+        };
+        let bindings = vec![(var.clone(), value)];
+        // Without a suffix, since the index is also used for tuple fields as in
+        // `self.I`, where `self.0usize` would not be valid.
+        let indices = (0..arity).map(|i| vec![Value::Int(i, None)]).collect();
+        let clauses = [(vec![index.clone()], indices)];
+        let body = expand_groups(&bindings, &clauses, body.clone())?;
+        expanded.extend(replace_value(&bindings, &[], body)?);
+    }
+    Ok(expanded)
+}
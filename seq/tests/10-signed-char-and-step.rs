@@ -0,0 +1,58 @@
+// Ranges are not limited to non-negative integers. Bounds can be negative, and
+// character ranges like 'a'..='e' substitute the loop variable with a char
+// literal, or paste the character itself into identifiers with `~`.
+//
+// Wrapping the range in parentheses allows a stride, in the same syntax as the
+// standard library: (0..64).step_by(16) produces 0, 16, 32 and 48. Only the
+// values stepped to are produced, so a huge range with a huge step is cheap,
+// and character ranges skip the surrogate code points like `char` ranges do.
+//
+// A negative value is substituted as a single operand, so `N.pow(2)` squares
+// -3 rather than negating 3 squared.
+
+use seq::seq;
+
+const OFFSETS: [i32; 6] = seq!(N in -3..3 {
+    [#(N,)*]
+});
+
+seq!(C in 'a'..='e' {
+    fn letters() -> String {
+        let mut letters = String::new();
+        #(
+            letters.push(C);
+        )*
+        letters
+    }
+
+    #(
+        const fn reg_~C() -> char {
+            C
+        }
+    )*
+});
+
+seq!(N in (0..64).step_by(16) {
+    const STRIDES: [u32; 4] = [#(N,)*];
+
+    #(
+        const LANE~N: u32 = N;
+    )*
+});
+
+const WIDE: [u32; 2] = seq!(N in (0..1 << 28).step_by(1 << 27) { [#(N,)*] });
+
+const AROUND_SURROGATES: [char; 2] = seq!(C in '\u{D7FF}'..='\u{E000}' { [#(C,)*] });
+
+fn main() {
+    assert_eq!(OFFSETS, [-3, -2, -1, 0, 1, 2]);
+    assert_eq!(letters(), "abcde");
+    assert_eq!(reg_a(), 'a');
+    assert_eq!(reg_e(), 'e');
+    assert_eq!(STRIDES, [0, 16, 32, 48]);
+    assert_eq!(LANE0 + LANE16 + LANE32 + LANE48, 96);
+    assert_eq!(WIDE, [0, 1 << 27]);
+    assert_eq!(AROUND_SURROGATES, ['\u{D7FF}', '\u{E000}']);
+    assert_eq!(seq!(N in -3i32..0 { [#(N.pow(2),)*] }), [9, 4, 1]);
+    assert_eq!(seq!(N in [-2i32] { N.abs() }), 2);
+}
@@ -0,0 +1,33 @@
+// Range bounds can be constant integer expressions, evaluated when the macro
+// expands. This matters most when the bounds come from a macro_rules macro,
+// where an `expr` fragment such as `4 * 2` reaches seq! as a single opaque
+// group rather than as a literal.
+//
+// The usual arithmetic operators + - * / % << >> are supported along with
+// unary minus and parentheses, following Rust's precedence rules.
+
+use seq::seq;
+
+macro_rules! lanes {
+    ($lanes:expr) => {
+        seq!(N in 0..$lanes {
+            const LANES: [usize; $lanes] = [#(N,)*];
+        });
+    };
+}
+
+lanes!(4 * 2);
+
+seq!(N in (1 << 2) - 1..=12 / 2 + 1 % 2 {
+    const MIDDLE: [u32; 5] = [#(N,)*];
+});
+
+seq!(N in (0..16 * 4).step_by(1 << 4) {
+    const STRIDES: [u32; 4] = [#(N,)*];
+});
+
+fn main() {
+    assert_eq!(LANES, [0, 1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(MIDDLE, [3, 4, 5, 6, 7]);
+    assert_eq!(STRIDES, [0, 16, 32, 48]);
+}
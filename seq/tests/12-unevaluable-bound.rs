@@ -0,0 +1,12 @@
+// A bound naming a constant cannot be evaluated when the macro expands, since
+// macros run before name resolution, so it is an error pointing at the bound.
+
+use seq::seq;
+
+const LANES: usize = 8;
+
+seq!(N in 0..LANES - 1 {
+    fn f~N() {}
+});
+
+fn main() {}
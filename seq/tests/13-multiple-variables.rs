@@ -0,0 +1,56 @@
+// Several variables can be bound at once, separated by commas, and the body is
+// repeated for every combination of their values, with the last variable
+// varying fastest as if the loops were nested in the order written:
+//
+//     seq!(I in 0..4, J in 0..4 { ... })
+//
+// A seq! invocation nested inside the body of another is left for the inner
+// macro to expand, after the outer variables have been substituted into it, so
+// the inner range and body can depend on the outer variable.
+
+use seq::seq;
+
+type Matrix = [[u32; 4]; 4];
+
+fn transpose(m: &Matrix) -> Matrix {
+    let mut t = [[0; 4]; 4];
+    seq!(I in 0..4, J in 0..4 {
+        t[J][I] = m[I][J];
+    });
+    t
+}
+
+seq!(I in 0..3, J in 0..2 {
+    const CELLS: [(u32, u32); 6] = [#((I, J),)*];
+
+    #(
+        const CELL~I~J: u32 = I * 10 + J;
+    )*
+});
+
+// Strictly lower triangle, with the inner range bounded by the outer variable.
+fn lower_triangle() -> Vec<(u32, u32)> {
+    let mut cells = Vec::new();
+    seq!(I in 1..4 {
+        seq!(J in 0..I {
+            #(
+                cells.push((I, J));
+            )*
+        });
+    });
+    cells
+}
+
+fn main() {
+    let m = [[1, 2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12], [13, 14, 15, 16]];
+    assert_eq!(transpose(&m)[0], [1, 5, 9, 13]);
+    assert_eq!(transpose(&m)[3], [4, 8, 12, 16]);
+
+    assert_eq!(CELLS, [(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (2, 1)]);
+    assert_eq!(CELL21, 21);
+
+    assert_eq!(
+        lower_triangle(),
+        [(1, 0), (2, 0), (2, 1), (3, 0), (3, 1), (3, 2)],
+    );
+}
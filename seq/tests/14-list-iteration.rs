@@ -0,0 +1,86 @@
+// Besides a range, a variable can iterate over a list of values in square
+// brackets. Elements that are not constants, such as types, are substituted
+// token for token, and an element spanning several tokens is kept together as
+// a single type or expression:
+//
+//     seq!(T in [u8, u16, Vec<u32>] { ... })
+//
+// A `<<` or `>>` in an element is a shift, not a pair of angle brackets, and
+// a `<` after a literal or a parenthesized expression is a comparison:
+//
+//     seq!(N in [1 << 2, 3, 4] { ... })    // three elements
+//     seq!(B in [1 < 2, true] { ... })     // two elements
+//
+// Several variables in parentheses iterate over a list of tuples in lockstep,
+// each variable taking its own position of the current tuple:
+//
+//     seq!((T, N) in [(u8, 8), (u16, 16)] { ... })
+
+use seq::seq;
+
+trait Bits {
+    const BITS: u32;
+}
+
+seq!((T, N) in [(u8, 8), (u16, 16), (u32, 32), (u64, 64)] {
+    #(
+        impl Bits for T {
+            const BITS: u32 = N;
+        }
+    )*
+});
+
+trait Name {
+    fn name() -> &'static str;
+}
+
+seq!(T in [bool, char, std::collections::HashMap<u8, u8>] {
+    #(
+        impl Name for T {
+            fn name() -> &'static str {
+                stringify!(T)
+            }
+        }
+    )*
+});
+
+seq!(T in [f32, f64], N in [2, 3] {
+    #(
+        const _: [T; N] = [0.0; N];
+    )*
+});
+
+seq!(X in [a, b, c] {
+    fn get_~X() -> &'static str {
+        stringify!(X)
+    }
+});
+
+seq!((NAME, VALUE) in [(ONE, 1), (TWO, 1 + 1)] {
+    #(
+        const NAME: i32 = VALUE * 10;
+    )*
+});
+
+const SHIFTED: [u32; 4] = seq!(N in [1 << 2, 3, 64 >> 4, std::mem::size_of::<Option<Option<u8>>>()] {
+    [#(N as u32),*]
+});
+
+const fn not(b: bool) -> bool {
+    !b
+}
+
+const COMPARED: [bool; 3] = seq!(B in [1 < 2, true, (1 + 1) < 1] {
+    [#(not(B)),*]
+});
+
+fn main() {
+    assert_eq!(SHIFTED, [4, 3, 4, 2]);
+    assert_eq!(COMPARED, [false, false, true]);
+    assert_eq!(<u8 as Bits>::BITS, 8);
+    assert_eq!(<u64 as Bits>::BITS, 64);
+    assert_eq!(<char as Name>::name(), "char");
+    assert_eq!(get_b(), "b");
+    assert_eq!(ONE, 10);
+    assert_eq!(TWO, 20);
+}
@@ -0,0 +1,89 @@
+// Pasting with `~` is not limited to appending the number to the end of an
+// identifier. Any chain of identifiers and variables joined by `~` is pasted
+// into one identifier, so the variable can come first or in the middle:
+//
+//     T~_MAX    pre~N~post
+//
+// A variable can be followed by a format in brackets, written like a format
+// spec, to zero-pad it to a width or write it in hex, octal or binary so that
+// names match a datasheet:
+//
+//     Reg~N~[02]     =>  Reg03
+//     IRQ~N~[02X]    =>  IRQ0B
+//
+// A `:` after a pasted identifier is left alone, so field initializers and
+// type ascriptions like `c~N: x` and `field~N: X` paste only the name.
+//
+// Inside a nested seq!, identifiers pasted with the inner variable are left for
+// the inner macro, so `cell~I~_~J` becomes `cell1_~J` in the outer expansion
+// and `cell1_2` in the inner one. An inner variable with the same name as an
+// outer one shadows it.
+
+#![allow(non_upper_case_globals)]
+
+use seq::seq;
+
+seq!(N in 0..4 {
+    #(
+        const Reg~N~[02]: u32 = N;
+        const pre_~N~_post: u32 = N * 3;
+    )*
+});
+
+seq!(T in [u8, u16] {
+    #(
+        const T~_MAX: T = T::MAX;
+    )*
+});
+
+seq!(N in 10..12 {
+    #(
+        const IRQ~N~[02X]: u32 = N;
+        const mask_~N~[x]: u32 = 1 << N;
+        const bit~N~[08b]: u32 = N;
+    )*
+});
+
+struct Pair<X> {
+    c0: X,
+    c1: X,
+}
+
+seq!(N in 0..2 {
+    fn pair<X: Copy>(x: X) -> Pair<X> {
+        Pair { #(c~N: x,)* }
+    }
+
+    struct Fields<X> {
+        #(field~N: X,)*
+    }
+});
+
+seq!(I in 0..2 {
+    seq!(J in 0..3 {
+        #(
+            const cell~I~_~J: u32 = I * 10 + J;
+        )*
+    });
+});
+
+seq!(N in 0..1 {
+    seq!(N in 5..6 {
+        const shadowed~N: u32 = N;
+    });
+});
+
+fn main() {
+    assert_eq!(Reg03, 3);
+    assert_eq!(u16_MAX, u16::MAX);
+    assert_eq!(pre_1_post, 3);
+    assert_eq!(IRQ0B, 11);
+    assert_eq!(mask_a, 1 << 10);
+    assert_eq!(bit00001011, 11);
+    assert_eq!(cell1_2, 12);
+    assert_eq!(shadowed5, 5);
+    let pair = pair(7);
+    assert_eq!((pair.c0, pair.c1), (7, 7));
+    let fields = Fields { field0: 'a', field1: 'b' };
+    assert_eq!((fields.field0, fields.field1), ('a', 'b'));
+}
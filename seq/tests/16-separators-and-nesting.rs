@@ -0,0 +1,69 @@
+// A repeated section can be followed by a separator before the `*`, as in
+// macro_rules, to put the separator between the copies but not after the last
+// one:
+//
+//     #( ... ),*    #( ... );*    #( ... )|*
+//
+// Sections can be nested when several variables are bound. The outermost
+// section repeats over the first variable, and the sections inside it repeat
+// over the variables bound after it, so a 2D array literal is written as
+//
+//     seq!(I in 0..2, J in 0..3 { [#([#((I, J)),*]),*] })
+//
+// An expression in `#( ... )` that is not followed by `*` is evaluated when the
+// macro expands and replaced by its value, with the loop variables available as
+// operands. Since `#(N + 1)*` would be read as a repeated section, multiply on
+// the left or inside the parentheses instead: `#((N + 1) * 2)`. Outside of the
+// repeated sections, where no loop variable is bound, an expression is still
+// evaluated, as long as it only has literal operands.
+
+use seq::seq;
+
+seq!(N in 0..4 {
+    const SQUARES: [u32; 4] = [#(N * N),*];
+
+    fn next(n: u32) -> u32 {
+        match n {
+            #(N => #(N + 1),)*
+            _ => 0,
+        }
+    }
+
+    fn is_small(n: u32) -> bool {
+        matches!(n, #(N)|*)
+    }
+
+    fn sum() -> u32 {
+        #(#((N + 1) * 2));*;
+        let mut total = 0;
+        #(total += N);*;
+        total
+    }
+});
+
+seq!(I in 0..2, J in 0..3 {
+    const GRID: [[(u32, u32); 3]; 2] = [#([#((I, J)),*]),*];
+});
+
+seq!(N in 0..2 {
+    const SIX: u32 = #(2 * 3);
+    const PAIR: [u32; 2] = [#(N * SIX),*];
+});
+
+seq!(N in 1..4 {
+    seq!(K in 0..N {
+        const LAST~N: u32 = [#(K),*][#(N - 1)];
+    });
+});
+
+fn main() {
+    assert_eq!(SQUARES, [0, 1, 4, 9]);
+    assert_eq!(next(2), 3);
+    assert!(is_small(3));
+    assert!(!is_small(4));
+    assert_eq!(sum(), 6);
+    assert_eq!(GRID[1], [(1, 0), (1, 1), (1, 2)]);
+    assert_eq!(SIX, 6);
+    assert_eq!(PAIR, [0, 6]);
+    assert_eq!(LAST3, 2);
+}
@@ -0,0 +1,67 @@
+// Arithmetic on the loop variable is often needed in the body, for example to
+// name the next field in a chain or to compute a bit offset. An expression in
+// `~( ... )` is evaluated when the macro expands, with the loop variables
+// available as operands.
+//
+// Pasted after an identifier it becomes part of that identifier, and takes a
+// format like a variable does:
+//
+//     field~(N + 1)      Reg~(N * 8)~[02]
+//
+// On its own it is replaced by its value as a literal, the same as `#( ... )`,
+// so it can be used wherever a literal can, including as a const generic
+// argument.
+
+use seq::seq;
+
+struct Chain {
+    field0: u32,
+    field1: u32,
+    field2: u32,
+    field3: u32,
+}
+
+seq!(N in 0..3 {
+    impl Chain {
+        fn next~N(&self) -> u32 {
+            self.field~(N + 1)
+        }
+    }
+});
+
+seq!(N in 0..3 {
+    #[allow(non_camel_case_types)]
+    struct Reg~(N * 8)~[02];
+
+    impl Reg~(N * 8)~[02] {
+        const OFFSET: u32 = ~(N * 8);
+    }
+});
+
+struct Bits<const N: usize>;
+
+impl<const N: usize> Bits<N> {
+    const WIDTH: usize = N;
+}
+
+seq!(N in 1..3 {
+    type Bits~N = Bits<~(N * 8)>;
+});
+
+fn main() {
+    let chain = Chain {
+        field0: 0,
+        field1: 1,
+        field2: 2,
+        field3: 3,
+    };
+    assert_eq!(chain.next0(), 1);
+    assert_eq!(chain.next2(), 3);
+
+    assert_eq!(Reg00::OFFSET, 0);
+    assert_eq!(Reg08::OFFSET, 8);
+    assert_eq!(Reg16::OFFSET, 16);
+
+    assert_eq!(Bits1::WIDTH, 8);
+    assert_eq!(Bits2::WIDTH, 16);
+}
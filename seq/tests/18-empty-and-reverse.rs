@@ -0,0 +1,41 @@
+// A range or list with no elements expands to nothing, so that a macro can
+// generate zero items for some configurations:
+//
+//     seq!(N in 0..COUNT { ... })    // nothing when COUNT is 0
+//
+// A parenthesized range can be reversed with `.rev()` to count down, and
+// combined with `.step_by(n)` in either order, with the same meaning as the
+// iterator methods: `(0..8).step_by(3).rev()` is 6, 3, 0 while
+// `(0..8).rev().step_by(3)` is 7, 4, 1.
+
+use seq::seq;
+
+seq!(N in 0..0 {
+    compile_error!("never expanded");
+});
+
+seq!(N in 4..2 {
+    compile_error!("never expanded");
+});
+
+seq!(T in [] {
+    compile_error!("never expanded");
+});
+
+const NONE: [u32; 0] = seq!(N in 0..0 { [#(N),*] });
+
+const DOWN: [u32; 4] = seq!(N in (0..4).rev() { [#(N),*] });
+
+const LETTERS: [char; 3] = seq!(C in ('a'..='c').rev() { [#(C),*] });
+
+const STEP_THEN_REV: [u32; 3] = seq!(N in (0..8).step_by(3).rev() { [#(N),*] });
+
+const REV_THEN_STEP: [u32; 3] = seq!(N in (0..8).rev().step_by(3) { [#(N),*] });
+
+fn main() {
+    assert_eq!(NONE, []);
+    assert_eq!(DOWN, [3, 2, 1, 0]);
+    assert_eq!(LETTERS, ['c', 'b', 'a']);
+    assert_eq!(STEP_THEN_REV, [6, 3, 0]);
+    assert_eq!(REV_THEN_STEP, [7, 4, 1]);
+}
@@ -0,0 +1,10 @@
+// Where an empty range is a mistake, starting the input with `#![deny_empty]`
+// turns it back into an error pointing at the range.
+
+use seq::seq;
+
+seq!(#![deny_empty] N in 8..8 {
+    fn f~N() {}
+});
+
+fn main() {}
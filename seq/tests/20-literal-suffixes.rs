@@ -0,0 +1,42 @@
+// The integers substituted for a loop variable are written without a suffix,
+// like `3`, and take whatever type the surrounding code infers. When the type
+// matters, give the range bounds a suffix, as in `0u8..4` or `0usize..4usize`,
+// and every substituted value keeps it. The result of an expression like
+// `#(N + 1)` keeps the suffix too, and so do suffixed list elements.
+//
+// Since `seq!` can be invoked wherever a macro can, it also works in
+// expression and pattern position, for example to write the arms of a match:
+//
+//     match byte {
+//         seq!(N in 0u8..4 { #(N)|* }) => "low",
+//         _ => "high",
+//     }
+
+use seq::seq;
+
+fn type_name_of<T>(_: T) -> &'static str {
+    std::any::type_name::<T>()
+}
+
+fn classify(byte: u8) -> &'static str {
+    match byte {
+        seq!(N in 0u8..4 { #(N)|* }) => "low",
+        _ => "high",
+    }
+}
+
+fn main() {
+    assert_eq!(type_name_of(seq!(N in 0u16..1 { N })), "u16");
+    assert_eq!(type_name_of(seq!(N in 0..1usize { N })), "usize");
+    assert_eq!(type_name_of(seq!(N in 0u64..1 { #(N + 1) })), "u64");
+    assert_eq!(type_name_of(seq!(N in 0i8..1 { #(-N - 1) })), "i8");
+    assert_eq!(type_name_of(seq!(N in [-1i8] { N })), "i8");
+    assert_eq!(type_name_of(seq!(N in 1u32..2 { #(1u8 << N) })), "u8");
+
+    let total = seq!(N in 0usize..4 { 0 #(+ N)* });
+    assert_eq!(type_name_of(total), "usize");
+    assert_eq!(total, 6);
+
+    assert_eq!(classify(2), "low");
+    assert_eq!(classify(9), "high");
+}
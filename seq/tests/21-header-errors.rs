@@ -0,0 +1,27 @@
+// Mistakes in the header of the macro are reported at the token where parsing
+// went wrong. When the input ends too early, the error points at the last token
+// there is, or at the closing delimiter of the group that ended.
+
+use seq::seq;
+
+seq!(0 in 0..8 {});
+
+seq!(N of 0..8 {});
+
+seq!(N in 0..8);
+
+seq!(N in 0..8 {} extra);
+
+seq!((A B) in [(1, 2)] {});
+
+seq!(#![] N in 0..8 {});
+
+seq!(#![deny_empty(yes)] N in 0..8 {});
+
+seq!(#![allow_empty] N in 0..8 {});
+
+seq!((A, B) in [(1, 2), 3] {});
+
+seq!(T in [a, , b] {});
+
+fn main() {}
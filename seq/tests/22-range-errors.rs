@@ -0,0 +1,36 @@
+// A range is `start..end` or `start..=end`, optionally in parentheses and
+// followed by `.step_by(n)` or `.rev()`. Mistakes in it are reported at the
+// offending token, with a suggestion where the intent is clear, such as the
+// `...` of C-style or old Rust inclusive ranges.
+
+use seq::seq;
+
+seq!(N in 0...8 {});
+
+seq!(N in 0 8 {});
+
+seq!(N in 8 {});
+
+seq!(N in ..8 {});
+
+seq!(N in 0.. {});
+
+seq!(N in 0..8, M in 0.. {});
+
+seq!(N in (0..8).step_by() {});
+
+seq!(N in (0..8).step_by(0) {});
+
+seq!(N in (0..8).step_by(1 << 64) {});
+
+seq!(N in (0..8).rev(1) {});
+
+seq!(N in (0..8).skip(1) {});
+
+seq!(N in (0..8).rev {});
+
+seq!(N in 0..'z' {});
+
+seq!(N in -(-170141183460469231731687303715884105727 - 1)..0 {});
+
+fn main() {}
@@ -0,0 +1,86 @@
+// Implementing a trait for tuples of every arity up to some limit needs a
+// growing prefix of type parameters rather than one item per number. The
+// companion macro `seq_tuples!` stamps out one copy of its body for each value
+// of `N`, and inside that copy its repeated sections run over a variable `I`
+// from 0 up to but excluding `N`:
+//
+//     seq_tuples!(N in 1..=3 {
+//         impl<#(T~I: Trait,)*> Trait for (#(T~I,)*) { ... }
+//     });
+//
+// expands to implementations for `(T0,)`, `(T0, T1)` and `(T0, T1, T2)`. `N`
+// itself can be used anywhere in the body, and `I` in expressions inside the
+// sections, as in `#(self.I.describe())*`. When `I` is taken, name the index
+// after the range instead, as in `seq_tuples!(N in 1..=3, J { ... })`. A suffix
+// on the range applies to `N` only, so that `self.I` stays a valid field.
+//
+// Inside a `seq!`, the sections and the index of a nested `seq_tuples!` belong
+// to it, while the variables of the `seq!` are substituted in its body.
+
+use seq::{seq, seq_tuples};
+
+trait Arity {
+    const ARITY: usize;
+
+    fn total(&self) -> u32;
+}
+
+impl Arity for u32 {
+    const ARITY: usize = 1;
+
+    fn total(&self) -> u32 {
+        *self
+    }
+}
+
+seq_tuples!(N in 0..=4 {
+    impl<#(T~I: Arity,)*> Arity for (#(T~I,)*) {
+        const ARITY: usize = N;
+
+        fn total(&self) -> u32 {
+            0 #(+ self.I.total())*
+        }
+    }
+});
+
+trait Count {
+    fn count(&self) -> usize;
+}
+
+seq_tuples!(I in 1usize..=3usize, J {
+    impl<#(T~J,)*> Count for (#(T~J,)*) {
+        fn count(&self) -> usize {
+            #(let _ = &self.J;)*
+            I
+        }
+    }
+});
+
+seq!(K in 1..3 {
+    trait Scaled~K {
+        fn scaled(&self) -> usize;
+    }
+
+    seq_tuples!(N in 0..=2 {
+        impl<#(T~I,)*> Scaled~K for (#(T~I,)*) {
+            fn scaled(&self) -> usize {
+                N * K
+            }
+        }
+    });
+});
+
+fn main() {
+    assert_eq!(<()>::ARITY, 0);
+    assert_eq!(<(u32,)>::ARITY, 1);
+    assert_eq!(<(u32, u32, u32, u32)>::ARITY, 4);
+
+    assert_eq!(().total(), 0);
+    assert_eq!((1,).total(), 1);
+    assert_eq!((1, 2, (3, 4)).total(), 10);
+
+    assert_eq!((1, 'a', "b").count(), 3);
+
+    assert_eq!(Scaled1::scaled(&()), 0);
+    assert_eq!(Scaled2::scaled(&(1, 'a')), 4);
+}
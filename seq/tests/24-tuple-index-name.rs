@@ -0,0 +1,13 @@
+// The index of `seq_tuples!` is called `I` unless it is named after the range.
+// Calling the arity `I` as well would make every `I` in the body ambiguous, so
+// it is an error pointing at the arity variable.
+
+use seq::seq_tuples;
+
+trait Trait {}
+
+seq_tuples!(I in 1..=3 {
+    impl<#(T~I,)*> Trait for (#(T~I,)*) {}
+});
+
+fn main() {}
@@ -0,0 +1,17 @@
+// Every level of nested repeated sections repeats over a variable of its own,
+// so a section inside another needs one more variable bound for it. And since
+// the loop variables are bound only inside the repeated sections, an
+// expression outside all of them cannot use one.
+
+use seq::seq;
+
+seq!(N in 0..3 {
+    const ONCE: [u32; 3] = [#( #(N)* ,)*];
+});
+
+seq!(N in 0..2 {
+    const NEXT: u32 = #(N + 1);
+    const ALL: [u32; 2] = [#(N),*];
+});
+
+fn main() {}
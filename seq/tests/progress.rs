@@ -10,4 +10,20 @@ fn tests() {
     t.pass("tests/07-inclusive-range.rs");
     t.compile_fail("tests/08-ident-span.rs");
     t.pass("tests/09-interaction-with-macrorules.rs");
+    t.pass("tests/10-signed-char-and-step.rs");
+    t.pass("tests/11-constant-expression-bounds.rs");
+    t.compile_fail("tests/12-unevaluable-bound.rs");
+    t.pass("tests/13-multiple-variables.rs");
+    t.pass("tests/14-list-iteration.rs");
+    t.pass("tests/15-paste-formats.rs");
+    t.pass("tests/16-separators-and-nesting.rs");
+    t.pass("tests/17-expression-paste.rs");
+    t.pass("tests/18-empty-and-reverse.rs");
+    t.compile_fail("tests/19-deny-empty.rs");
+    t.pass("tests/20-literal-suffixes.rs");
+    t.compile_fail("tests/21-header-errors.rs");
+    t.compile_fail("tests/22-range-errors.rs");
+    t.pass("tests/23-tuple-impls.rs");
+    t.compile_fail("tests/24-tuple-index-name.rs");
+    t.compile_fail("tests/25-section-errors.rs");
 }